@@ -0,0 +1,359 @@
+use crate::particle_gen::Orbital;
+use crate::particle_gen::wavefunction::Wavefunction;
+use std::f64::consts::PI;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/* NOTE:
+    Plain 2D charts of the pieces of a state, for slides and handouts. They are
+    written straight out as SVG text so there's nothing to pull in, and every
+    chart is self contained with its own axes and labels.
+*/
+
+const WIDTH: f64 = 640.;
+const HEIGHT: f64 = 420.;
+const MARGIN: f64 = 60.;
+const POLAR_SIZE: f64 = 440.;
+const SAMPLES: usize = 400;
+const CURVE_COLOR: &str = "#5a5af0";
+const AXIS_COLOR: &str = "#333333";
+const GRID_COLOR: &str = "#dddddd";
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum RadialPlot {
+    /// R(r)
+    Wavefunction,
+    /// r² R(r)², the probability of finding the electron at radius r
+    Probability,
+}
+
+impl RadialPlot {
+    fn label(self) -> &'static str {
+        match self {
+            RadialPlot::Wavefunction => "R(r)",
+            RadialPlot::Probability => "r²R(r)²",
+        }
+    }
+
+    fn file_prefix(self) -> &'static str {
+        match self {
+            RadialPlot::Wavefunction => "radial",
+            RadialPlot::Probability => "radial_probability",
+        }
+    }
+}
+
+/// (r, value) pairs from the nucleus out to the extent of the state, r in a0
+pub fn radial_series(
+    wavefunction: &Wavefunction,
+    plot: RadialPlot,
+    samples: usize,
+) -> Vec<(f64, f64)> {
+    let extent = wavefunction.extent();
+    (0..samples)
+        .map(|i| {
+            let r = extent * i as f64 / (samples - 1) as f64;
+            let radial = wavefunction.radial(r);
+            let value = match plot {
+                RadialPlot::Wavefunction => radial,
+                RadialPlot::Probability => r * r * radial * radial,
+            };
+            (r, value)
+        })
+        .collect()
+}
+
+/// |Y_lm(θ)|² around the xz plane, θ from +z. It doesn't depend on φ.
+pub fn angular_series(wavefunction: &Wavefunction, samples: usize) -> Vec<(f64, f64)> {
+    (0..samples)
+        .map(|i| {
+            let theta = 2. * PI * i as f64 / samples as f64;
+            (theta, wavefunction.ylm(theta, 0.).norm_sqr())
+        })
+        .collect()
+}
+
+pub fn radial_chart(wavefunction: &Wavefunction, plot: RadialPlot) -> String {
+    let series = radial_series(wavefunction, plot, SAMPLES);
+    let x_max = series.last().map_or(1., |(r, _)| *r);
+    let y_min = series.iter().map(|(_, v)| *v).fold(0., f64::min);
+    let y_max = series.iter().map(|(_, v)| *v).fold(0., f64::max);
+    let y_pad = 0.05 * (y_max - y_min).max(f64::EPSILON);
+    let (y_min, y_max) = (y_min - if y_min < 0. { y_pad } else { 0. }, y_max + y_pad);
+
+    let (left, right, top, bottom) = (MARGIN, WIDTH - MARGIN / 2., MARGIN, HEIGHT - MARGIN);
+    let to_x = |x: f64| left + (right - left) * x / x_max;
+    let to_y = |y: f64| bottom - (bottom - top) * (y - y_min) / (y_max - y_min);
+
+    let mut svg = svg_open(WIDTH, HEIGHT);
+    let title = format!(
+        "{} for n = {}, l = {}",
+        plot.label(),
+        wavefunction.n(),
+        wavefunction.l()
+    );
+    text(&mut svg, WIDTH / 2., MARGIN / 2., "middle", 16., &title);
+
+    // grid and ticks
+    let x_step = nice_step(x_max, 8);
+    for i in 0..=(x_max / x_step) as usize {
+        let x = i as f64 * x_step;
+        line(&mut svg, to_x(x), top, to_x(x), bottom, GRID_COLOR, 1.);
+        text(
+            &mut svg,
+            to_x(x),
+            bottom + 18.,
+            "middle",
+            12.,
+            &format_tick(x),
+        );
+    }
+    let y_step = nice_step(y_max - y_min, 6);
+    let first = (y_min / y_step).ceil() as i64;
+    let last = (y_max / y_step).floor() as i64;
+    for i in first..=last {
+        let y = i as f64 * y_step;
+        line(&mut svg, left, to_y(y), right, to_y(y), GRID_COLOR, 1.);
+        text(
+            &mut svg,
+            left - 6.,
+            to_y(y) + 4.,
+            "end",
+            12.,
+            &format_tick(y),
+        );
+    }
+
+    // axes, the horizontal one through zero since R(r) goes negative
+    line(&mut svg, left, top, left, bottom, AXIS_COLOR, 1.5);
+    line(&mut svg, left, to_y(0.), right, to_y(0.), AXIS_COLOR, 1.5);
+    text(
+        &mut svg,
+        (left + right) / 2.,
+        HEIGHT - 16.,
+        "middle",
+        13.,
+        "r (a0)",
+    );
+    let _ = writeln!(
+        svg,
+        r#"<text x="0" y="0" text-anchor="middle" font-size="13" transform="translate(16 {:.2}) rotate(-90)">{}</text>"#,
+        (top + bottom) / 2.,
+        plot.label()
+    );
+
+    let points: Vec<(f64, f64)> = series.iter().map(|(x, y)| (to_x(*x), to_y(*y))).collect();
+    polyline(&mut svg, &points, CURVE_COLOR, "none");
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+pub fn polar_chart(wavefunction: &Wavefunction) -> String {
+    let series = angular_series(wavefunction, SAMPLES);
+    let max = series.iter().map(|(_, v)| *v).fold(0., f64::max);
+    let centre = POLAR_SIZE / 2.;
+    let radius = centre - MARGIN;
+
+    let mut svg = svg_open(POLAR_SIZE, POLAR_SIZE);
+    let title = format!(
+        "|Y(θ)|² for l = {}, m = {}",
+        wavefunction.l(),
+        wavefunction.m()
+    );
+    text(&mut svg, centre, MARGIN / 2., "middle", 16., &title);
+
+    // rings at half and full of the maximum, and the x and z axes
+    for frac in [0.5, 1.] {
+        let _ = writeln!(
+            svg,
+            r#"<circle cx="{centre:.2}" cy="{centre:.2}" r="{:.2}" fill="none" stroke="{GRID_COLOR}"/>"#,
+            frac * radius
+        );
+    }
+    line(
+        &mut svg,
+        centre - radius,
+        centre,
+        centre + radius,
+        centre,
+        AXIS_COLOR,
+        1.,
+    );
+    line(
+        &mut svg,
+        centre,
+        centre - radius,
+        centre,
+        centre + radius,
+        AXIS_COLOR,
+        1.,
+    );
+    text(
+        &mut svg,
+        centre + radius + 10.,
+        centre + 4.,
+        "start",
+        13.,
+        "x",
+    );
+    text(&mut svg, centre, centre - radius - 8., "middle", 13., "z");
+    text(
+        &mut svg,
+        centre + radius * 0.7,
+        centre + radius + 30.,
+        "middle",
+        12.,
+        &format!("outer ring = {}", format_tick(max)),
+    );
+
+    // z points up the page, and θ is measured from it
+    let points: Vec<(f64, f64)> = series
+        .iter()
+        .map(|(theta, v)| {
+            let rho = if max > 0. { radius * v / max } else { 0. };
+            (centre + rho * theta.sin(), centre - rho * theta.cos())
+        })
+        .collect();
+    polyline(&mut svg, &points, CURVE_COLOR, "#5a5af033");
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// writes R(r), r²R² and |Y|² charts for the state into `dir`, and returns
+/// the paths it wrote
+pub fn export_charts(wavefunction: &Wavefunction, dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let (n, l, m) = (wavefunction.n(), wavefunction.l(), wavefunction.m());
+    let mut written = Vec::new();
+    for plot in [RadialPlot::Wavefunction, RadialPlot::Probability] {
+        let path = dir.join(format!("{}_{n}{l}.svg", plot.file_prefix()));
+        std::fs::write(&path, radial_chart(wavefunction, plot))?;
+        written.push(path);
+    }
+    let path = dir.join(format!("angular_{l}{m}.svg"));
+    std::fs::write(&path, polar_chart(wavefunction))?;
+    written.push(path);
+    Ok(written)
+}
+
+fn svg_open(width: f64, height: f64) -> String {
+    format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" "#,
+            r#"viewBox="0 0 {w} {h}" font-family="sans-serif">"#,
+            "\n",
+            r#"<rect width="{w}" height="{h}" fill="white"/>"#,
+            "\n"
+        ),
+        w = width,
+        h = height
+    )
+}
+
+fn line(svg: &mut String, x1: f64, y1: f64, x2: f64, y2: f64, color: &str, width: f64) {
+    let _ = writeln!(
+        svg,
+        r#"<line x1="{x1:.2}" y1="{y1:.2}" x2="{x2:.2}" y2="{y2:.2}" stroke="{color}" stroke-width="{width}"/>"#
+    );
+}
+
+fn text(svg: &mut String, x: f64, y: f64, anchor: &str, size: f64, content: &str) {
+    let _ = writeln!(
+        svg,
+        r#"<text x="{x:.2}" y="{y:.2}" text-anchor="{anchor}" font-size="{size}">{content}</text>"#
+    );
+}
+
+fn polyline(svg: &mut String, points: &[(f64, f64)], stroke: &str, fill: &str) {
+    let points: Vec<String> = points
+        .iter()
+        .map(|(x, y)| format!("{x:.2},{y:.2}"))
+        .collect();
+    let _ = writeln!(
+        svg,
+        r#"<polyline points="{}" fill="{fill}" stroke="{stroke}" stroke-width="2"/>"#,
+        points.join(" ")
+    );
+}
+
+// 1, 2 or 5 times a power of ten, giving roughly `count` ticks over `range`
+pub(crate) fn nice_step(range: f64, count: usize) -> f64 {
+    if range <= 0. {
+        return 1.;
+    }
+    let raw = range / count as f64;
+    let magnitude = 10_f64.powf(raw.log10().floor());
+    let step = [1., 2., 5., 10.]
+        .into_iter()
+        .find(|s| s * magnitude >= raw)
+        .unwrap_or(10.);
+    step * magnitude
+}
+
+pub(crate) fn format_tick(value: f64) -> String {
+    if value == 0. {
+        return "0".to_string();
+    }
+    if value.abs() >= 0.01 && value.abs() < 1000. {
+        let text = format!("{value:.3}");
+        return text.trim_end_matches('0').trim_end_matches('.').to_string();
+    }
+    format!("{value:.1e}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_radial_peak() {
+        // the most likely radius of a circular orbit (l = n - 1) is n² a0 / Z
+        for (n, z) in [(1, 1), (2, 1), (3, 1), (1, 2), (2, 3)] {
+            let wavefunction = Wavefunction::new(n, n - 1, 0, 1. / z as f64);
+            let series = radial_series(&wavefunction, RadialPlot::Probability, 2000);
+            let (peak, _) = series
+                .iter()
+                .copied()
+                .fold((0., 0.), |best, p| if p.1 > best.1 { p } else { best });
+            let expected = (n * n) as f64 / z as f64;
+            assert!((peak - expected).abs() < 0.05 * expected, "{peak}");
+        }
+    }
+
+    #[test]
+    fn test_angular_normalised() {
+        // ∫|Y|² dΩ = 1, the series is φ independent so integrate over θ only
+        let wavefunction = Wavefunction::new(3, 2, 1, 1.0);
+        let series = angular_series(&wavefunction, 4000);
+        let dtheta = 2. * PI / 4000.;
+        let total: f64 = series
+            .iter()
+            .filter(|(theta, _)| *theta <= PI)
+            .map(|(theta, v)| v * theta.sin() * dtheta * 2. * PI)
+            .sum();
+        assert!((total - 1.).abs() < 1e-2, "{total}");
+    }
+
+    #[test]
+    fn test_svg() {
+        let wavefunction = Wavefunction::new(3, 1, 1, 1.0);
+        for svg in [
+            radial_chart(&wavefunction, RadialPlot::Wavefunction),
+            radial_chart(&wavefunction, RadialPlot::Probability),
+            polar_chart(&wavefunction),
+        ] {
+            assert!(svg.starts_with("<svg"));
+            assert!(svg.trim_end().ends_with("</svg>"));
+            assert_eq!(svg.matches("<polyline").count(), 1);
+            assert!(!svg.contains("NaN"));
+        }
+    }
+
+    #[test]
+    fn test_nice_step() {
+        assert_eq!(nice_step(10., 5), 2.);
+        assert_eq!(nice_step(0.9, 6), 0.2);
+        assert_eq!(nice_step(73., 8), 10.);
+    }
+}
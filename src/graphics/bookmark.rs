@@ -0,0 +1,146 @@
+use super::camera::{Camera, check_view};
+use super::rows::parse_rows;
+use anyhow::bail;
+use cgmath::{Deg, Point3, Vector3};
+use std::fmt::Write;
+
+// where bookmarks are kept between sessions, next to the charts and paths,
+// and on the web the key they are kept under in the browser's local storage
+pub const BOOKMARK_FILE: &str = "camera_bookmarks.txt";
+// one for each digit key
+pub const SLOTS: usize = 10;
+
+/* NOTE:
+    Bookmarks are saved a slot to a line, as the slot's digit, then eye,
+    target and up as x y z each, then the vertical field of view in degrees:
+
+        1  0 0 30  0 0 0  0 1 0  45
+
+    Slots that were never set are left out, and a line that wouldn't give
+    a view the camera can take is an error rather than a broken view later.
+*/
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Pose {
+    pub eye: Point3<f32>,
+    pub target: Point3<f32>,
+    pub up: Vector3<f32>,
+    pub fovy: Deg<f32>,
+}
+
+impl Pose {
+    pub fn of(camera: &Camera) -> Pose {
+        Pose {
+            eye: camera.eye,
+            target: camera.target,
+            up: camera.up,
+            fovy: camera.fovy.into(),
+        }
+    }
+
+    pub fn apply(&self, camera: &mut Camera) {
+        camera.eye = self.eye;
+        camera.target = self.target;
+        camera.up = self.up;
+        camera.fovy = self.fovy.into();
+    }
+}
+
+#[derive(Default)]
+pub struct Bookmarks {
+    slots: [Option<Pose>; SLOTS],
+}
+
+impl Bookmarks {
+    pub fn parse(text: &str) -> anyhow::Result<Bookmarks> {
+        let mut bookmarks = Bookmarks::default();
+        for (line, [slot, v @ ..]) in parse_rows::<11>(text, "a slot, eye, target, up and fov")? {
+            if slot.fract() != 0. || !(0. ..SLOTS as f32).contains(&slot) {
+                bail!("line {line}: no slot {slot}");
+            }
+            let pose = Pose {
+                eye: Point3::new(v[0], v[1], v[2]),
+                target: Point3::new(v[3], v[4], v[5]),
+                up: Vector3::new(v[6], v[7], v[8]),
+                fovy: Deg(v[9]),
+            };
+            if let Err(e) = check_view(pose.eye, pose.target, pose.up, pose.fovy) {
+                bail!("line {line}: {e}");
+            }
+            bookmarks.slots[slot as usize] = Some(pose);
+        }
+        Ok(bookmarks)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("# slot  eye x y z  target x y z  up x y z  fov\n");
+        for (slot, pose) in self.slots.iter().enumerate() {
+            let Some(Pose {
+                eye: e,
+                target: t,
+                up: u,
+                fovy,
+            }) = pose
+            else {
+                continue;
+            };
+            let _ = writeln!(
+                text,
+                "{slot}  {} {} {}  {} {} {}  {} {} {}  {}",
+                e.x, e.y, e.z, t.x, t.y, t.z, u.x, u.y, u.z, fovy.0
+            );
+        }
+        text
+    }
+
+    pub fn get(&self, slot: usize) -> Option<Pose> {
+        self.slots.get(slot).copied().flatten()
+    }
+
+    pub fn set(&mut self, slot: usize, pose: Pose) {
+        self.slots[slot] = Some(pose);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Bookmarks, Pose};
+    use crate::graphics::camera::Camera;
+
+    #[test]
+    fn test_bookmarks() {
+        let mut camera = Camera::new();
+        camera.eye = (1.5, -2., 30.25).into();
+        camera.up = cgmath::Vector3::unit_z();
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.set(3, Pose::of(&camera));
+
+        // every pose comes back exactly, and the unset slots stay unset
+        let text = bookmarks.to_text();
+        let read = Bookmarks::parse(&text).unwrap();
+        assert_eq!(read.get(3), Some(Pose::of(&camera)));
+        assert_eq!(read.get(0), None);
+        assert_eq!(read.get(10), None);
+
+        let mut other = Camera::new();
+        read.get(3).unwrap().apply(&mut other);
+        assert_eq!(other.eye, camera.eye);
+        assert!((other.fovy.0 - camera.fovy.0).abs() < 1e-6);
+
+        assert!(Bookmarks::parse("10  0 0 30  0 0 0  0 1 0  45").is_err());
+        assert!(Bookmarks::parse("1  0 0 30  0 0 0  0 1 0").is_err());
+        // views the camera can't take, from the second line of the file
+        for bad in [
+            "1  0 0 0  0 0 0  0 1 0  45",
+            "1  0 0 30  0 0 0  0 0 1  45",
+            "1  0 0 30  0 0 0  0 0 0  45",
+            "1  0 0 30  0 0 0  0 1 0  -5",
+            "1  0 0 30  0 0 0  0 1 0  180",
+            "1  0 0 inf  0 0 0  0 1 0  45",
+            "1  0 0 30  0 NaN 0  0 1 0  45",
+        ] {
+            let error = Bookmarks::parse(&format!("{text}{bad}")).err().unwrap();
+            assert!(error.to_string().starts_with("line 3: "), "{error}");
+        }
+    }
+}
@@ -1,22 +1,41 @@
 use std::f32::consts::PI;
 
 use cgmath::*;
+
+use super::slice::{ClipPlane, MAX_CLIP_PLANES};
 use winit::keyboard::KeyCode;
 
 const TARG_NEAR_DIST_LIMIT: f32 = 1.;
 const TARG_VERT_ANGLE_LIMIT: f32 = (PI / 2.) - 0.1;
-const RESET_Z: f32 = 3.;
+const RESET_Z: f32 = 30.;
+// orbit per pixel dragged, and pan per pixel as a fraction of the distance
+// to the target
+const DRAG_TURN: f32 = 0.005;
+const PAN_PER_PIXEL: f32 = 0.0015;
+// how much closer one line of the wheel brings the target
+const ZOOM_PER_LINE: f32 = 1.1;
+// the least a pinch can scale the view by in one go, fingers brought right
+// together would otherwise send the camera off to infinity
+const MIN_PINCH: f32 = 0.1;
+// seconds for the camera to take up most of a key's speed, or to lose most
+// of its own once let go
+pub const DEFAULT_INERTIA: f32 = 0.2;
+// a longer frame, as after a stall, moves no further than one this long
+const MAX_STEP: f32 = 0.1;
+// seconds for a full turn of the turntable, and how high above the xy plane
+// it starts a view that was looking down z
+const TURNTABLE_PERIOD: f32 = 20.;
+const TURNTABLE_ELEVATION: Deg<f32> = Deg(20.);
 
 pub struct Camera {
     pub eye: cgmath::Point3<f32>,
-    //pub yaw: Rad<f32>,
-    //pub pitch: Rad<f32>,
     pub target: cgmath::Point3<f32>,
     pub up: cgmath::Vector3<f32>,
     pub aspect: f32,
     pub fovy: Rad<f32>,
     pub znear: f32,
     pub zfar: f32,
+    pub orthographic: bool,
 }
 
 #[rustfmt::skip]
@@ -27,38 +46,85 @@ pub const OPENGL_TO_WGPU_MATRIX: cgmath::Matrix4<f32> = cgmath::Matrix4::from_co
     cgmath::Vector4::new(0.0, 0.0, 0.5, 1.0),
 );
 
+impl Default for Camera {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Camera {
     pub fn new() -> Camera {
         Camera {
             eye: (0.0, 0.0, RESET_Z).into(),
             target: (0.0, 0.0, 0.).into(),
-            //yaw: cgmath::Deg(90.0).into(),
-            //pitch: cgmath::Deg(0.0).into(),
             up: cgmath::Vector3::unit_y(),
             aspect: 1.,
             fovy: cgmath::Deg(45.0).into(),
             znear: 0.5,
-            zfar: 50.0,
+            zfar: 200.0,
+            orthographic: false,
         }
     }
+
+    /// how much of the world the view spans vertically at the target. The
+    /// orthographic view keeps to it, so switching projection keeps the
+    /// orbital the same size.
+    pub fn view_height(&self) -> f32 {
+        2. * (self.target - self.eye).magnitude() * (self.fovy.0 / 2.).tan()
+    }
+
+    /// looks at the target from along `direction`, as far away as before,
+    /// with `up` towards the top of the view
+    pub fn look_from(&mut self, direction: Vector3<f32>, up: Vector3<f32>) {
+        let distance = (self.target - self.eye).magnitude();
+        self.eye = self.target + direction.normalize() * distance;
+        self.up = up;
+    }
+
     pub fn build_view_projection_matrix(&self) -> cgmath::Matrix4<f32> {
-        //let (sin_pitch, cos_pitch) = self.pitch.0.sin_cos();
-        //let (sin_yaw, cos_yaw) = self.yaw.0.sin_cos();
-
-        //let view = cgmath::Matrix4::look_to_rh(
-        //    self.eye,
-        //    cgmath::Vector3::new(cos_pitch * cos_yaw, sin_pitch, cos_pitch * sin_yaw).normalize(),
-        //    Vector3::unit_y(),
-        //);
         let view = cgmath::Matrix4::look_at_rh(self.eye, self.target, self.up);
-        let proj = perspective(self.fovy, self.aspect, self.znear, self.zfar);
-        return OPENGL_TO_WGPU_MATRIX * proj * view;
+        let proj = if self.orthographic {
+            // the depth range reaches behind the eye, nothing in front of
+            // the target's side of the view is lost zooming in
+            let top = self.view_height() / 2.;
+            let right = top * self.aspect;
+            ortho(-right, right, -top, top, -self.zfar, self.zfar)
+        } else {
+            perspective(self.fovy, self.aspect, self.znear, self.zfar)
+        };
+        OPENGL_TO_WGPU_MATRIX * proj * view
     }
 
     pub fn update_aspect(&mut self, newaspect: f32) {
         self.aspect = newaspect;
     }
 }
+/// why the camera can't be put at `eye` looking at `target`, if it can't.
+/// For files the camera is read from, anything else would leave the view
+/// with no direction or no way up, and NaN from there on.
+pub fn check_view(
+    eye: Point3<f32>,
+    target: Point3<f32>,
+    up: Vector3<f32>,
+    fovy: Deg<f32>,
+) -> Result<(), &'static str> {
+    let numbers = [eye.to_vec(), target.to_vec(), up];
+    if numbers.iter().any(|v| !v.is_finite()) || !fovy.0.is_finite() {
+        return Err("every number has to be finite");
+    }
+    if fovy.0 <= 0. || fovy.0 >= 180. {
+        return Err("the field of view has to be between 0 and 180°");
+    }
+    let direction = target - eye;
+    if direction.magnitude() < 1e-6 {
+        return Err("the eye and target are the same point");
+    }
+    if up.magnitude() < 1e-6 || direction.normalize().cross(up.normalize()).magnitude() < 1e-3 {
+        return Err("up has to point away from the view direction");
+    }
+    Ok(())
+}
+
 // We need this for Rust to store our data correctly for the shaders
 #[repr(C)]
 // This is so we can store this in a buffer
@@ -71,7 +137,22 @@ pub struct CameraUniform {
     eye: [f32; 3],
     aspect: f32,
     view_proj: [[f32; 4]; 4],
-    //empty: [f32; 1], // for byte alignment
+    // world space screen axes, billboards are spanned by these
+    right: [f32; 3],
+    _pad0: f32, // for byte alignment
+    up: [f32; 3],
+    _pad1: f32,
+    // (normal, offset) of each clip plane, only the first num_clip_planes
+    // are used
+    clip_planes: [[f32; 4]; MAX_CLIP_PLANES],
+    num_clip_planes: u32,
+    _pad2: [u32; 3],
+}
+
+impl Default for CameraUniform {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl CameraUniform {
@@ -82,20 +163,69 @@ impl CameraUniform {
             eye: cgmath::vec3(0., 0., 0.).into(),
             aspect: 0.,
             view_proj: cgmath::Matrix4::identity().into(),
+            right: cgmath::vec3(1., 0., 0.).into(),
+            _pad0: 0.,
+            up: cgmath::vec3(0., 1., 0.).into(),
+            _pad1: 0.,
+            clip_planes: [[0.; 4]; MAX_CLIP_PLANES],
+            num_clip_planes: 0,
+            _pad2: [0; 3],
         }
     }
 
     // replace view_proj with new projection
     pub fn update_view_proj(&mut self, camera: &Camera) {
         self.view_proj = camera.build_view_projection_matrix().into();
-        self.aspect = camera.aspect.into();
+        self.aspect = camera.aspect;
         self.eye = camera.eye.into();
+
+        let forward = (camera.target - camera.eye).normalize();
+        let right = forward.cross(camera.up).normalize();
+        self.right = right.into();
+        self.up = right.cross(forward).into();
+    }
+
+    pub fn update_clip_planes(&mut self, planes: &[ClipPlane]) {
+        let planes = &planes[..planes.len().min(MAX_CLIP_PLANES)];
+        for (slot, plane) in self.clip_planes.iter_mut().zip(planes) {
+            *slot = plane.equation();
+        }
+        self.num_clip_planes = planes.len() as u32;
     }
 }
 
+/* NOTE:
+    Speeds are per second, and each update moves by however long the frame
+    took, so the camera moves the same on any display.
+
+    The camera carries its speed from frame to frame: held keys ease it up to
+    theirs, and once let go it coasts to a stop, over about `inertia`
+    seconds. A drag moves the camera by exactly the drag, and leaves it
+    moving as fast as the drag was, so a flick keeps it turning for a moment.
+    With no inertia it starts and stops dead, like before.
+
+    Flying, W S A D E Q move the eye along where it looks and the mouse turns
+    it on the spot. The target is carried along ahead of the eye, so either
+    mode takes over from the other where it left off.
+*/
+/// what the keys and mouse do: turn around the target, or look and fly
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum CameraMode {
+    Orbit,
+    Fly,
+}
+
 pub struct CameraController {
+    mode: CameraMode,
+    turntable: bool,
+    // distance units and turns per second
     speed: f32,
     t_speed: f32,
+    inertia: f32,
+    velocity: Vector3<f32>,
+    radial_speed: f32,
+    yaw_speed: f32,
+    pitch_speed: f32,
     forward: f32,
     backward: f32,
     right: f32,
@@ -104,13 +234,23 @@ pub struct CameraController {
     down: f32,
     t_h: f32,
     t_v: f32,
+    p_h: f32,
+    p_v: f32,
+    zoom: f32,
 }
 
 impl CameraController {
     pub fn new(speed: f32, turn_speed: f32) -> Self {
         Self {
-            speed: speed,
+            mode: CameraMode::Orbit,
+            turntable: false,
+            speed,
             t_speed: turn_speed,
+            inertia: DEFAULT_INERTIA,
+            velocity: Vector3::zero(),
+            radial_speed: 0.,
+            yaw_speed: 0.,
+            pitch_speed: 0.,
             forward: 0.,
             backward: 0.,
             right: 0.,
@@ -119,6 +259,9 @@ impl CameraController {
             down: 0.,
             t_h: 0.,
             t_v: 0.,
+            p_h: 0.,
+            p_v: 0.,
+            zoom: 1.,
         }
     }
 
@@ -153,73 +296,382 @@ impl CameraController {
         }
     }
 
-    pub fn reset(&self, cam: &mut Camera) {
-        cam.eye.x = 0.;
-        cam.eye.y = 0.;
-        cam.eye.z = RESET_Z;
-        //cam.yaw = cgmath::Deg(90.0).into();
-        //cam.pitch = cgmath::Deg(0.0).into();
+    /// puts the camera back where it starts, at rest and off the turntable.
+    /// The projection and the inertia are settings, and stay as they are.
+    pub fn reset(&mut self, cam: &mut Camera) {
+        let start = Camera::new();
+        cam.eye = start.eye;
+        cam.target = start.target;
+        cam.up = start.up;
+        cam.fovy = start.fovy;
+        self.turntable = false;
+        self.stop();
+        // and nothing dragged or scrolled since the last update is applied
+        (self.t_h, self.t_v, self.p_h, self.p_v) = (0., 0., 0., 0.);
+        self.zoom = 1.;
     }
 
+    // mouse movement adds up until the next update, there can be several
+    // events a frame
     pub fn turn(&mut self, mouse_dx: f64, mouse_dy: f64) {
-        self.t_h = mouse_dx as f32;
-        self.t_v = mouse_dy as f32;
+        self.t_h += mouse_dx as f32;
+        self.t_v += mouse_dy as f32;
+    }
+
+    pub fn pan(&mut self, mouse_dx: f64, mouse_dy: f64) {
+        self.p_h += mouse_dx as f32;
+        self.p_v += mouse_dy as f32;
+    }
+
+    /// lines of the wheel, positive towards the target
+    pub fn scroll(&mut self, lines: f32) {
+        self.zoom *= ZOOM_PER_LINE.powf(lines);
+    }
+
+    /// a pinch's change in magnification, positive spreading the fingers
+    pub fn pinch(&mut self, delta: f32) {
+        self.zoom *= (1. + delta).max(MIN_PINCH);
+    }
+
+    pub fn inertia(&self) -> f32 {
+        self.inertia
     }
 
-    pub fn update_camera(&mut self, camera: &mut Camera) {
-        use cgmath::InnerSpace;
+    pub fn set_inertia(&mut self, seconds: f32) {
+        self.inertia = seconds.max(0.);
+    }
+
+    pub fn mode(&self) -> CameraMode {
+        self.mode
+    }
 
-        // target based
+    /// switches between orbiting and flying, from where the camera is and
+    /// facing the way it faces
+    pub fn toggle_mode(&mut self) {
+        self.mode = match self.mode {
+            CameraMode::Orbit => CameraMode::Fly,
+            CameraMode::Fly => CameraMode::Orbit,
+        };
+        self.stop();
+    }
+
+    pub fn turntable(&self) -> bool {
+        self.turntable
+    }
 
-        {
+    /// starts or stops turning the camera around the z axis through the
+    /// target, which it starts by standing z up
+    pub fn toggle_turntable(&mut self, camera: &mut Camera) {
+        self.turntable = !self.turntable;
+        if !self.turntable {
+            return;
+        }
+        let direction = (camera.eye - camera.target).normalize();
+        // looking along z there would be nothing to turn
+        if direction.z.abs() > TURNTABLE_ELEVATION.cos() {
+            let (sin, cos) = Rad::from(TURNTABLE_ELEVATION).0.sin_cos();
+            camera.look_from(Vector3::new(cos, 0., sin), Vector3::unit_z());
+        } else {
+            camera.up = Vector3::unit_z();
+        }
+    }
+
+    /// drops whatever speed the camera was coasting with
+    pub fn stop(&mut self) {
+        self.radial_speed = 0.;
+        self.yaw_speed = 0.;
+        self.pitch_speed = 0.;
+        self.velocity = Vector3::zero();
+    }
+
+    /// moves the camera on by `dt` seconds
+    pub fn update_camera(&mut self, camera: &mut Camera, dt: f32) {
+        let dt = dt.clamp(0., MAX_STEP);
+        // how much of the way to the keys' speed this frame goes
+        let follow = if self.inertia > 0. {
+            1. - (-dt / self.inertia).exp()
+        } else {
+            1.
+        };
+
+        // panning moves the target, and the eye with it, across the view so
+        // the scene follows the cursor
+        if self.p_h != 0. || self.p_v != 0. {
             let forward = camera.target - camera.eye;
-            let forward_norm = forward.normalize();
-            let forward_mag = forward.magnitude();
-
-            if (self.forward - self.backward) != 0. {
-                let radius_move = (self.forward
-                    * (forward_mag > TARG_NEAR_DIST_LIMIT) as i32 as f32)
-                    - self.backward;
-                // casting looks stupid but it has to be done this way in 1.89
-                camera.eye += radius_move * forward_norm * self.speed;
-            }
+            let right = forward.cross(camera.up).normalize();
+            let up = right.cross(forward).normalize();
+            let shift = (up * self.p_v - right * self.p_h) * forward.magnitude() * PAN_PER_PIXEL;
+            camera.target += shift;
+            camera.eye += shift;
+            self.p_h = 0.;
+            self.p_v = 0.;
+        }
 
-            let radius = camera.eye - camera.target;
-            let xz_rotate = cgmath::Rad(self.right - self.left) * self.t_speed * 2. * PI; // rotations per frame
-            let xz_h = (radius.x.powi(2) + radius.z.powi(2)).sqrt();
-            let angle = cgmath::Rad::atan2(radius.z, radius.x) + xz_rotate;
-            camera.eye.z = angle.sin() * xz_h + camera.target.z;
-            camera.eye.x = angle.cos() * xz_h + camera.target.x;
+        match self.mode {
+            CameraMode::Orbit => self.orbit(camera, dt, follow),
+            CameraMode::Fly => self.fly(camera, dt, follow),
+        }
+        if self.turntable {
+            let turn = Quaternion::from_angle_z(Rad(2. * PI * dt / TURNTABLE_PERIOD));
+            camera.eye = camera.target + turn.rotate_vector(camera.eye - camera.target);
+        }
+        self.t_h = 0.;
+        self.t_v = 0.;
+        self.zoom = 1.;
+    }
+
+    // target based
+    fn orbit(&mut self, camera: &mut Camera, dt: f32, follow: f32) {
+        let forward = camera.target - camera.eye;
+        let forward_norm = forward.normalize();
+        let forward_mag = forward.magnitude();
 
+        ease(
+            &mut self.radial_speed,
+            (self.forward - self.backward) * self.speed,
+            follow,
+        );
+        let radius_move =
+            (self.radial_speed * dt).min((forward_mag - TARG_NEAR_DIST_LIMIT).max(0.));
+        camera.eye += radius_move * forward_norm;
+
+        if self.zoom != 1. {
             let radius = camera.eye - camera.target;
-            let mag = radius.magnitude();
-            let yplane_rotate = cgmath::Rad(self.down - self.up) * self.t_speed * 2. * PI; // rotations per frame
-            let angle = cgmath::Rad::atan2(radius.y, xz_h) + yplane_rotate;
-            if !(angle.0 > TARG_VERT_ANGLE_LIMIT || angle.0 < -TARG_VERT_ANGLE_LIMIT) {
-                camera.eye.y = angle.sin() * mag + camera.target.y;
-                let ratio = angle.cos() * mag / xz_h;
-                camera.eye.x = ratio * radius.x + camera.target.x;
-                camera.eye.z = ratio * radius.z + camera.target.z;
+            let distance = (radius.magnitude() / self.zoom).max(TARG_NEAR_DIST_LIMIT);
+            camera.eye = camera.target + radius.normalize() * distance;
+        }
+
+        // keys turn by rotations per second, dragging by the pixels moved
+        let (yaw, pitch) = if self.t_h != 0. || self.t_v != 0. {
+            let turned = (self.t_h * DRAG_TURN, self.t_v * DRAG_TURN);
+            if dt > 0. {
+                (self.yaw_speed, self.pitch_speed) = (turned.0 / dt, turned.1 / dt);
+            }
+            turned
+        } else {
+            let turn = self.t_speed * 2. * PI;
+            ease(&mut self.yaw_speed, (self.right - self.left) * turn, follow);
+            ease(&mut self.pitch_speed, (self.down - self.up) * turn, follow);
+            (self.yaw_speed * dt, self.pitch_speed * dt)
+        };
+
+        let (radius, stopped) = turn(camera.eye - camera.target, camera.up, yaw, pitch);
+        camera.eye = camera.target + radius;
+        // stopping at the pole stops the turn towards it
+        if stopped {
+            self.pitch_speed = 0.;
+        }
+    }
+
+    // direction based, the target stays the same distance ahead of the eye
+    // so orbiting picks up around it
+    fn fly(&mut self, camera: &mut Camera, dt: f32, follow: f32) {
+        let ahead = camera.target - camera.eye;
+        let distance = ahead.magnitude();
+        // looking follows the mouse, right and down
+        let (forward, _) = turn(
+            ahead / distance,
+            camera.up,
+            self.t_h * DRAG_TURN,
+            -self.t_v * DRAG_TURN,
+        );
+        let right = forward.cross(camera.up).normalize();
+
+        let wanted = (forward * (self.forward - self.backward)
+            + right * (self.right - self.left)
+            + camera.up * (self.up - self.down))
+            * self.speed;
+        for axis in 0..3 {
+            ease(&mut self.velocity[axis], wanted[axis], follow);
+        }
+        camera.eye += self.velocity * dt;
+        // the wheel moves ahead as far as it would bring the target closer
+        if self.zoom != 1. {
+            camera.eye += forward * distance * (1. - 1. / self.zoom);
+        }
+        camera.target = camera.eye + forward * distance;
+    }
+}
+
+// `v` turned `yaw` clockwise around `up` as seen from above, and tilted
+// `pitch` towards it, stopping short of the poles. Whether it had to stop is
+// returned with it.
+fn turn(v: Vector3<f32>, up: Vector3<f32>, yaw: f32, pitch: f32) -> (Vector3<f32>, bool) {
+    let up = up.normalize();
+    let v = Quaternion::from_axis_angle(up, Rad(-yaw)).rotate_vector(v);
+    let elevation = (v.dot(up) / v.magnitude()).clamp(-1., 1.).asin();
+    let wanted = elevation + pitch;
+    let reached = wanted.clamp(-TARG_VERT_ANGLE_LIMIT, TARG_VERT_ANGLE_LIMIT);
+    let axis = v.cross(up);
+    if axis.magnitude2() == 0. {
+        return (v, false);
+    }
+    let v =
+        Quaternion::from_axis_angle(axis.normalize(), Rad(reached - elevation)).rotate_vector(v);
+    (v, reached != wanted)
+}
+
+fn ease(speed: &mut f32, wanted: f32, follow: f32) {
+    *speed += (wanted - *speed) * follow;
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        Camera, CameraController, CameraMode, MIN_PINCH, TARG_NEAR_DIST_LIMIT,
+        TARG_VERT_ANGLE_LIMIT, TURNTABLE_PERIOD,
+    };
+    use cgmath::InnerSpace;
+    use winit::keyboard::KeyCode;
+
+    #[test]
+    fn test_drag_orbit() {
+        let mut camera = Camera::new();
+        let mut controller = CameraController::new(12., 0.6);
+
+        // dragging orbits at the same distance, and can't go over the pole
+        controller.turn(100., 0.);
+        controller.turn(100., 0.);
+        controller.update_camera(&mut camera, 0.);
+        assert!((camera.eye.z - 30. * 1f32.cos()).abs() < 1e-3);
+        assert!(((camera.eye - camera.target).magnitude() - 30.).abs() < 1e-3);
+        controller.turn(0., 1e4);
+        controller.update_camera(&mut camera, 0.);
+        let radius = camera.eye - camera.target;
+        assert!(((radius.y / 30.).asin() - TARG_VERT_ANGLE_LIMIT).abs() < 1e-3);
+
+        // the wheel stops short of the target
+        controller.scroll(100.);
+        controller.update_camera(&mut camera, 0.);
+        let distance = (camera.eye - camera.target).magnitude();
+        assert!((distance - TARG_NEAR_DIST_LIMIT).abs() < 1e-4);
+
+        // panning moves the target and eye together, and reset undoes it
+        controller.pan(50., 0.);
+        controller.update_camera(&mut camera, 0.);
+        assert!(camera.target.x.abs() + camera.target.z.abs() > 0.);
+        assert!(((camera.eye - camera.target).magnitude() - distance).abs() < 1e-4);
+        controller.reset(&mut camera);
+        assert_eq!(camera.target, (0., 0., 0.).into());
+        assert!(((camera.eye - camera.target).magnitude() - 30.).abs() < 1e-4);
+
+        // pinching the fingers right together only backs off so far
+        controller.pinch(-1.);
+        controller.update_camera(&mut camera, 0.);
+        let distance = (camera.eye - camera.target).magnitude();
+        assert!(distance.is_finite() && distance <= 30. / MIN_PINCH + 1e-2);
+    }
+
+    #[test]
+    fn test_frame_rate() {
+        // a second of a held key turns as far at 60 frames a second as at
+        // 144 when there is no inertia
+        let turned = |frames: usize| {
+            let mut camera = Camera::new();
+            let mut controller = CameraController::new(12., 0.6);
+            controller.set_inertia(0.);
+            controller.process_events(KeyCode::KeyD, true);
+            for _ in 0..frames {
+                controller.update_camera(&mut camera, 1. / frames as f32);
             }
+            camera.eye
+        };
+        let (slow, fast) = (turned(60), turned(144));
+        assert!((slow - fast).magnitude() < 1e-3);
+    }
+
+    #[test]
+    fn test_inertia() {
+        // with inertia the camera coasts after the key is let go, then stops
+        let mut camera = Camera::new();
+        let mut controller = CameraController::new(12., 0.6);
+        controller.process_events(KeyCode::KeyD, true);
+        controller.update_camera(&mut camera, 0.1);
+        controller.process_events(KeyCode::KeyD, false);
+        let before = camera.eye;
+        controller.update_camera(&mut camera, 0.05);
+        assert!((camera.eye - before).magnitude() > 1e-3);
+        for _ in 0..100 {
+            controller.update_camera(&mut camera, 0.1);
         }
+        let before = camera.eye;
+        controller.update_camera(&mut camera, 0.1);
+        assert!((camera.eye - before).magnitude() < 1e-4);
+    }
 
-        // direction based
-        {
-            //let (yaw_sin, yaw_cos) = camera.yaw.0.sin_cos();
-            //let forward = Vector3::new(yaw_cos, 0.0, yaw_sin).normalize();
-            //let right = Vector3::new(-yaw_sin, 0.0, yaw_cos).normalize();
-            //
-            //camera.eye += forward * (self.forward - self.backward) * self.speed;
-            //camera.eye += right * (self.right - self.left) * self.speed;
-            //camera.eye += camera.up * (self.up - self.down) * self.speed;
-            //
-            //let d_yaw = Rad(self.t_h * self.t_speed as f32);
-            //camera.yaw -= d_yaw;
-            //self.t_h = 0.;
-            //
-            //let d_pitch = Rad(self.t_v * self.t_speed as f32);
-            //camera.pitch += d_pitch;
-            //self.t_v = 0.;
+    #[test]
+    fn test_fly_mode() {
+        // flying starts from the same view, and moves the target along
+        let mut camera = Camera::new();
+        let mut controller = CameraController::new(12., 0.6);
+        controller.set_inertia(0.);
+        camera.eye = (3., 4., 20.).into();
+        controller.toggle_mode();
+        assert_eq!(controller.mode(), CameraMode::Fly);
+        controller.update_camera(&mut camera, 0.1);
+        assert!((camera.eye - cgmath::point3(3., 4., 20.)).magnitude() < 1e-4);
+        assert!((camera.target - cgmath::point3(0., 0., 0.)).magnitude() < 1e-3);
+        controller.process_events(KeyCode::KeyW, true);
+        controller.update_camera(&mut camera, 0.1);
+        controller.process_events(KeyCode::KeyW, false);
+        let distance = (camera.target - camera.eye).magnitude();
+        assert!((distance - cgmath::vec3(3., 4., 20.).magnitude()).abs() < 1e-3);
+        assert!(camera.eye.z < 20.);
+        // and orbiting goes on around wherever the target got to
+        let (eye, target) = (camera.eye, camera.target);
+        controller.toggle_mode();
+        controller.update_camera(&mut camera, 0.1);
+        assert!((camera.eye - eye).magnitude() < 1e-4);
+        assert_eq!(camera.target, target);
+    }
+
+    #[test]
+    fn test_orthographic() {
+        // an orthographic side view puts the top of the view height at the
+        // top of the view however deep it is, and keeps z up
+        let mut camera = Camera::new();
+        let mut controller = CameraController::new(12., 0.6);
+        camera.orthographic = true;
+        camera.look_from(cgmath::Vector3::unit_x(), cgmath::Vector3::unit_z());
+        assert!((camera.eye - cgmath::point3(30., 0., 0.)).magnitude() < 1e-4);
+        let top = camera.view_height() / 2.;
+        let view_proj = camera.build_view_projection_matrix();
+        for depth in [-10., 0., 10.] {
+            let clip = view_proj * cgmath::vec4(depth, 0., top, 1.);
+            assert!((clip.y / clip.w - 1.).abs() < 1e-4);
         }
+        // and orbiting goes around z from there
+        controller.turn(100., 0.);
+        controller.update_camera(&mut camera, 0.);
+        assert!(camera.eye.z.abs() < 1e-4 && camera.eye.y.abs() > 1.);
+    }
+
+    #[test]
+    fn test_turntable() {
+        // the turntable stands a view down z on its side, then goes around
+        // z once a period at the same height
+        let mut camera = Camera::new();
+        let mut controller = CameraController::new(12., 0.6);
+        controller.toggle_turntable(&mut camera);
+        assert!(controller.turntable());
+        let height = camera.eye.z;
+        assert!(height > 1. && height < 15.);
+        let start = camera.eye;
+        for _ in 0..(TURNTABLE_PERIOD * 20.) as usize {
+            controller.update_camera(&mut camera, 0.05);
+        }
+        assert!((camera.eye - start).magnitude() < 1e-2);
+        controller.update_camera(&mut camera, 0.05);
+        assert!((camera.eye.z - height).abs() < 1e-3);
+        assert!((camera.eye - start).magnitude() > 0.1);
+
+        // reset stops it, and takes back anything else done to the view
+        camera.fovy = cgmath::Deg(20.).into();
+        controller.reset(&mut camera);
+        assert!(!controller.turntable());
+        assert_eq!(camera.fovy, Camera::new().fovy);
+        let eye = camera.eye;
+        controller.update_camera(&mut camera, 0.05);
+        assert_eq!(camera.eye, eye);
     }
 }
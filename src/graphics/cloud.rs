@@ -0,0 +1,97 @@
+use super::colormap::ColorMap;
+use super::instance::Instance;
+use crate::particle_gen::Particle;
+use crate::particle_gen::spinor::Spin;
+
+const SPIN_UP_COLOR: [f32; 3] = [0.9, 0.25, 0.2];
+const SPIN_DOWN_COLOR: [f32; 3] = [0.15, 0.45, 0.95];
+
+// which cloud is shown: the plain |nlm⟩ state, or the spin orbital built on
+// it with its spin components coloured apart or together
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SpinView {
+    Off,
+    Separate,
+    Total,
+}
+
+impl SpinView {
+    pub fn next(self) -> SpinView {
+        match self {
+            SpinView::Off => SpinView::Separate,
+            SpinView::Separate => SpinView::Total,
+            SpinView::Total => SpinView::Off,
+        }
+    }
+}
+
+// how the orbital is drawn: each particle as a lit icosahedron, or as a
+// camera facing sprite that fades out and adds up with its neighbours, or no
+// particles at all and the density raymarched straight from ψ
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum RenderMode {
+    Mesh,
+    Billboard,
+    Volume,
+}
+
+impl RenderMode {
+    pub const ALL: [RenderMode; 3] = [RenderMode::Mesh, RenderMode::Billboard, RenderMode::Volume];
+
+    pub fn next(self) -> RenderMode {
+        match self {
+            RenderMode::Mesh => RenderMode::Billboard,
+            RenderMode::Billboard => RenderMode::Volume,
+            RenderMode::Volume => RenderMode::Mesh,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RenderMode::Mesh => "mesh",
+            RenderMode::Billboard => "billboard",
+            RenderMode::Volume => "volume",
+        }
+    }
+}
+
+// how the particles are drawn from |ψ|²: inverting a tabulated CDF, or
+// keeping uniform points in proportion to the density
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Sampler {
+    InverseCdf,
+    Rejection,
+}
+
+impl Sampler {
+    pub const ALL: [Sampler; 2] = [Sampler::InverseCdf, Sampler::Rejection];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Sampler::InverseCdf => "inverse CDF",
+            Sampler::Rejection => "rejection",
+        }
+    }
+}
+
+// the spin colours take over from the colour map while they are shown
+pub fn instances(
+    particles: &[Particle],
+    view: SpinView,
+    map: ColorMap,
+    radius: f32,
+) -> Vec<Instance> {
+    particles
+        .iter()
+        .zip(map.colors(particles))
+        .map(|(p, color)| Instance {
+            position: p.position.map(|x| x as f32),
+            radius,
+            color: match (view, p.spin) {
+                (SpinView::Separate, Some(Spin::Up)) => SPIN_UP_COLOR,
+                (SpinView::Separate, Some(Spin::Down)) => SPIN_DOWN_COLOR,
+                _ => color,
+            },
+        })
+        .collect()
+}
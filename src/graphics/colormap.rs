@@ -0,0 +1,323 @@
+use super::vertex::Vertex;
+use crate::particle_gen::Particle;
+use std::f64::consts::PI;
+
+const PLAIN_COLOR: [f32; 3] = [0.5, 0.5, 1.0];
+const POSITIVE_COLOR: [f32; 3] = [0.9, 0.2, 0.15];
+const NEGATIVE_COLOR: [f32; 3] = [0.1, 0.35, 0.9];
+// the density spans orders of magnitude, the gradient covers this many below
+// the densest particle
+const DENSITY_DECADES: f64 = 3.;
+
+// legend bar on the right of the screen, in clip space
+pub const LEGEND_X: (f32, f32) = (0.86, 0.9);
+pub const LEGEND_Y: (f32, f32) = (-0.6, 0.6);
+const LEGEND_STEPS: usize = 48;
+
+// matplotlib's perceptually uniform maps sampled at nine even stops, in sRGB
+#[rustfmt::skip]
+const VIRIDIS: [u32; 9] = [0x440154, 0x472d7b, 0x3b528b, 0x2c728e, 0x21918c, 0x28ae80, 0x5ec962, 0xaddc30, 0xfde725];
+#[rustfmt::skip]
+const MAGMA: [u32; 9] = [0x000004, 0x1c1044, 0x4f127b, 0x812581, 0xb5367a, 0xe55064, 0xfb8761, 0xfec287, 0xfcfdbf];
+#[rustfmt::skip]
+const PLASMA: [u32; 9] = [0x0d0887, 0x4c02a1, 0x7e03a8, 0xa92395, 0xcc4778, 0xe56b5d, 0xf89441, 0xfdc328, 0xf0f921];
+#[rustfmt::skip]
+const INFERNO: [u32; 9] = [0x000004, 0x1f0c48, 0x550f6d, 0x88226a, 0xba3655, 0xe35933, 0xf98e09, 0xf9cb35, 0xfcffa4];
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Gradient {
+    Viridis,
+    Magma,
+    Plasma,
+    Inferno,
+}
+
+impl Gradient {
+    pub fn next(self) -> Gradient {
+        match self {
+            Gradient::Viridis => Gradient::Magma,
+            Gradient::Magma => Gradient::Plasma,
+            Gradient::Plasma => Gradient::Inferno,
+            Gradient::Inferno => Gradient::Viridis,
+        }
+    }
+
+    /// linear RGB at t in 0..=1
+    pub fn sample(self, t: f64) -> [f32; 3] {
+        let stops = match self {
+            Gradient::Viridis => &VIRIDIS,
+            Gradient::Magma => &MAGMA,
+            Gradient::Plasma => &PLASMA,
+            Gradient::Inferno => &INFERNO,
+        };
+        let x = t.clamp(0., 1.) * (stops.len() - 1) as f64;
+        let i = (x as usize).min(stops.len() - 2);
+        let frac = (x - i as f64) as f32;
+        let (a, b) = (hex_to_linear(stops[i]), hex_to_linear(stops[i + 1]));
+        [0, 1, 2].map(|c| a[c] + (b[c] - a[c]) * frac)
+    }
+}
+
+// what the particle colours show. Everything is worked out from the
+// particle's own position and ψ, so it works for any sampled orbital.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ColorMap {
+    Plain,
+    // sign of Re ψ, for complex states that is the cos(mφ) real orbital's sign
+    Sign,
+    // arg ψ around the hue circle
+    Phase,
+    Density(Gradient),
+    Radius(Gradient),
+}
+
+impl ColorMap {
+    /// the next kind of map, the gradient carries over
+    pub fn next(self) -> ColorMap {
+        match self {
+            ColorMap::Plain => ColorMap::Sign,
+            ColorMap::Sign => ColorMap::Phase,
+            ColorMap::Phase => ColorMap::Density(Gradient::Viridis),
+            ColorMap::Density(g) => ColorMap::Radius(g),
+            ColorMap::Radius(_) => ColorMap::Plain,
+        }
+    }
+
+    /// the same map with the next gradient, maps without one are unchanged
+    pub fn next_gradient(self) -> ColorMap {
+        match self {
+            ColorMap::Density(g) => ColorMap::Density(g.next()),
+            ColorMap::Radius(g) => ColorMap::Radius(g.next()),
+            other => other,
+        }
+    }
+
+    /// names of the kinds of map, in the order `next` goes through them
+    pub const KINDS: [&'static str; 5] = ["plain", "sign", "phase", "density", "radius"];
+
+    /// the position of this map's kind in KINDS
+    pub fn kind(self) -> usize {
+        match self {
+            ColorMap::Plain => 0,
+            ColorMap::Sign => 1,
+            ColorMap::Phase => 2,
+            ColorMap::Density(_) => 3,
+            ColorMap::Radius(_) => 4,
+        }
+    }
+
+    /// the map of a kind from KINDS, with `gradient` if it takes one
+    pub fn from_kind(kind: usize, gradient: Gradient) -> ColorMap {
+        match kind {
+            0 => ColorMap::Plain,
+            1 => ColorMap::Sign,
+            2 => ColorMap::Phase,
+            3 => ColorMap::Density(gradient),
+            _ => ColorMap::Radius(gradient),
+        }
+    }
+
+    /// the gradient for views that always need one, viridis if the map
+    /// doesn't have its own
+    pub fn gradient(self) -> Gradient {
+        match self {
+            ColorMap::Density(g) | ColorMap::Radius(g) => g,
+            _ => Gradient::Viridis,
+        }
+    }
+
+    pub fn colors(self, particles: &[Particle]) -> Vec<[f32; 3]> {
+        match self {
+            ColorMap::Plain => vec![PLAIN_COLOR; particles.len()],
+            ColorMap::Sign => particles.iter().map(|p| sign_color(p.psi.re)).collect(),
+            ColorMap::Phase => particles
+                .iter()
+                .map(|p| hue((p.psi.arg() + PI) / (2. * PI)))
+                .collect(),
+            ColorMap::Density(gradient) => {
+                let max = particles
+                    .iter()
+                    .map(|p| p.psi.norm_sqr())
+                    .fold(0., f64::max);
+                particles
+                    .iter()
+                    .map(|p| {
+                        // nothing to scale by when every particle sits on a node
+                        if max > 0. {
+                            let decades = (p.psi.norm_sqr() / max).log10();
+                            gradient.sample(1. + decades / DENSITY_DECADES)
+                        } else {
+                            gradient.sample(0.)
+                        }
+                    })
+                    .collect()
+            }
+            ColorMap::Radius(gradient) => {
+                let radius = |p: &Particle| p.position.iter().map(|x| x * x).sum::<f64>().sqrt();
+                let max = particles.iter().map(radius).fold(0., f64::max);
+                particles
+                    .iter()
+                    .map(|p| {
+                        if max > 0. {
+                            gradient.sample(radius(p) / max)
+                        } else {
+                            gradient.sample(0.)
+                        }
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// what the bottom and top of the legend stand for
+    pub fn legend_range(self) -> Option<(&'static str, &'static str)> {
+        match self {
+            ColorMap::Plain => None,
+            ColorMap::Sign => Some(("ψ < 0", "ψ > 0")),
+            ColorMap::Phase => Some(("arg ψ = -π", "arg ψ = π")),
+            ColorMap::Density(_) => Some(("|ψ|² / 1000", "max |ψ|²")),
+            ColorMap::Radius(_) => Some(("r = 0", "max r")),
+        }
+    }
+
+    /// a TriangleList colour bar in clip space, bottom to top the same way as
+    /// `legend_range`
+    pub fn legend_vertices(self) -> Vec<Vertex> {
+        let color_at = |t: f64| match self {
+            ColorMap::Plain => PLAIN_COLOR,
+            ColorMap::Sign if t < 0.5 => NEGATIVE_COLOR,
+            ColorMap::Sign => POSITIVE_COLOR,
+            ColorMap::Phase => hue(t),
+            ColorMap::Density(g) | ColorMap::Radius(g) => g.sample(t),
+        };
+        if self == ColorMap::Plain {
+            return Vec::new();
+        }
+
+        let (x0, x1) = LEGEND_X;
+        let (y0, y1) = LEGEND_Y;
+        let mut vertices = Vec::with_capacity(LEGEND_STEPS * 6);
+        for i in 0..LEGEND_STEPS {
+            // each band is flat, so the sign legend gets a hard edge
+            let t = (i as f64 + 0.5) / LEGEND_STEPS as f64;
+            let color = color_at(t);
+            let bottom = y0 + (y1 - y0) * i as f32 / LEGEND_STEPS as f32;
+            let top = y0 + (y1 - y0) * (i + 1) as f32 / LEGEND_STEPS as f32;
+            for (x, y) in [
+                (x0, bottom),
+                (x1, bottom),
+                (x1, top),
+                (x0, bottom),
+                (x1, top),
+                (x0, top),
+            ] {
+                vertices.push(Vertex {
+                    position: [x, y, 0.],
+                    color,
+                });
+            }
+        }
+        vertices
+    }
+}
+
+pub fn sign_color(value: f64) -> [f32; 3] {
+    if value >= 0. {
+        POSITIVE_COLOR
+    } else {
+        NEGATIVE_COLOR
+    }
+}
+
+// the surface is sRGB, so colours given in sRGB have to be linearised
+fn hex_to_linear(hex: u32) -> [f32; 3] {
+    [16, 8, 0].map(|shift| {
+        let c = ((hex >> shift) & 0xff) as f32 / 255.;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    })
+}
+
+/// the other way round, for writing linear colours into sRGB textures
+pub fn to_srgb8(color: [f32; 3]) -> [u8; 4] {
+    let [r, g, b] = color.map(|c| {
+        let c = c.clamp(0., 1.);
+        let srgb = if c <= 0.0031308 {
+            c * 12.92
+        } else {
+            1.055 * c.powf(1. / 2.4) - 0.055
+        };
+        (srgb * 255.).round() as u8
+    });
+    [r, g, b, 255]
+}
+
+// fully saturated hue, t in 0..1 goes once around the colour wheel
+fn hue(t: f64) -> [f32; 3] {
+    let h = t.rem_euclid(1.) * 6.;
+    let x = (1. - (h % 2. - 1.).abs()) as f32;
+    match h as u32 {
+        0 => [1., x, 0.],
+        1 => [x, 1., 0.],
+        2 => [0., 1., x],
+        3 => [0., x, 1.],
+        4 => [x, 0., 1.],
+        _ => [1., 0., x],
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ColorMap, Gradient, hue};
+    use crate::particle_gen::Particle;
+    use num::complex::Complex64;
+
+    #[test]
+    fn test_color_maps() {
+        let close = |a: [f32; 3], b: [f32; 3]| a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-4);
+
+        // the hue wheel closes on itself
+        assert!(close(hue(0.), hue(1.)));
+        assert!(close(hue(0.), [1., 0., 0.]));
+        assert!(close(hue(1. / 3.), [0., 1., 0.]));
+
+        // the ends of viridis, linearised
+        assert!(close(
+            Gradient::Viridis.sample(0.),
+            [0.0578, 0.0003, 0.0887]
+        ));
+        assert!(close(
+            Gradient::Viridis.sample(2.),
+            Gradient::Viridis.sample(1.)
+        ));
+
+        let particle = |x: f64, psi: Complex64| Particle {
+            position: [x, 0., 0.],
+            psi,
+            spin: None,
+        };
+        let particles = [
+            particle(1., Complex64::new(1., 0.)),
+            particle(4., Complex64::new(-0.01, 0.)),
+        ];
+        let colors = ColorMap::Density(Gradient::Magma).colors(&particles);
+        assert!(close(colors[0], Gradient::Magma.sample(1.)));
+        // 10⁻⁴ of the max is below the bottom of the gradient
+        assert!(close(colors[1], Gradient::Magma.sample(0.)));
+        let colors = ColorMap::Sign.colors(&particles);
+        assert_ne!(colors[0], colors[1]);
+
+        // a cloud all on a node, or all at the nucleus, has nothing to scale
+        // by and goes to the bottom of the gradient
+        let nodes = [particle(0., Complex64::new(0., 0.))];
+        for map in [
+            ColorMap::Density(Gradient::Magma),
+            ColorMap::Radius(Gradient::Magma),
+        ] {
+            assert!(close(map.colors(&nodes)[0], Gradient::Magma.sample(0.)));
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use super::vertex::Vertex;
+use crate::particle_gen::FlowSample;
+use cgmath::*;
+
+const ARROW_COLOR: [f32; 3] = [1.0, 0.45, 0.1];
+const HEAD_FRAC: f32 = 0.35;
+
+// builds a LineList of arrows for the probability current, scaled so the
+// strongest current is drawn `length` long
+pub fn arrow_vertices(samples: &[FlowSample], length: f32) -> Vec<Vertex> {
+    let to_vec = |v: [f64; 3]| vec3(v[0] as f32, v[1] as f32, v[2] as f32);
+    let max_current = samples
+        .iter()
+        .map(|s| to_vec(s.current).magnitude())
+        .fold(0., f32::max);
+    if max_current == 0. {
+        return Vec::new();
+    }
+
+    let mut vertices = Vec::with_capacity(samples.len() * 6);
+    let mut push = |p: Vector3<f32>| {
+        vertices.push(Vertex {
+            position: p.into(),
+            color: ARROW_COLOR,
+        })
+    };
+    for sample in samples {
+        let current = to_vec(sample.current);
+        let mag = current.magnitude();
+        if mag / max_current < 0.01 {
+            continue;
+        }
+        let dir = current / mag;
+        let tail = to_vec(sample.position);
+        let tip = tail + dir * length * mag / max_current;
+
+        // the head opens in the plane spanned by the arrow and the z axis
+        // normal, which for a circulating current is the cylindrical radius
+        let side = if dir.cross(Vector3::unit_z()).magnitude() > 0.1 {
+            dir.cross(Vector3::unit_z()).normalize()
+        } else {
+            dir.cross(Vector3::unit_x()).normalize()
+        };
+        let head = (tip - tail) * HEAD_FRAC;
+
+        push(tail);
+        push(tip);
+        push(tip);
+        push(tip - head + side * head.magnitude() * 0.5);
+        push(tip);
+        push(tip - head - side * head.magnitude() * 0.5);
+    }
+    vertices
+}
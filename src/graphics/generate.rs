@@ -0,0 +1,630 @@
+use super::cloud::{Sampler, SpinView};
+use super::flow;
+use super::isosurface::{Grid, Isosurface};
+use super::vertex::Vertex;
+use crate::particle_gen::spinor::{self, SpinOrbital};
+use crate::particle_gen::wavefunction::Wavefunction;
+use crate::particle_gen::{
+    BoundSearch, CDFTriple, CdfBuilder, Orbital, Particle, gen_current_field, sample_particles,
+    sample_particles_rejection,
+};
+use std::collections::VecDeque;
+
+// the states that can be asked for. n + l stays within what the factorials
+// in the normalisation can hold.
+pub const MAX_N: i32 = 10;
+pub const MAX_Z: i32 = 10;
+const FLOW_RESO: i32 = 15;
+const FLOW_CUTOFF: f64 = 0.05;
+const CLOUD_RESO: i32 = 64;
+const ISO_FRACTION: f64 = 0.9;
+const ISO_RESO: usize = 64;
+// tables kept for recently visited states, at CLOUD_RESO each is a few MB
+const CDF_CACHE_SIZE: usize = 16;
+// particles sampled between looks for a newer request, and on the web per
+// frame
+const CHUNK_SIZE: usize = 4096;
+// the most points a step of building a table or the isosurface evaluates ψ
+// at, which takes about as long as sampling a chunk
+const STEP_POINTS: usize = 4 * CHUNK_SIZE;
+
+/* NOTE:
+    Everything drawn from the state that takes more than a moment to work
+    out, the cloud, the isosurface and the flow arrows, is made away from the
+    frame loop. Natively a worker thread takes requests and sends back what
+    they generate, and the frame loop picks the results up when they are
+    ready. If requests come in faster than they are done, as when a key is
+    held to step through states, the worker skips to the newest.
+
+    The cloud comes back in chunks, so a big one fills in over a few frames
+    instead of appearing all at once. The first chunk of a request replaces
+    the cloud and carries the state's geometry if that changed, the rest add
+    to it. Between chunks the worker looks for a newer request, and drops
+    what is left of the current one if there is.
+
+    The worker keeps the CDF tables of the last few states it sampled. |ψ|²
+    doesn't depend on the sign of m, so stepping m to -m reuses the table as
+    well as coming back to a state.
+
+    Before the first chunk a request needs the table its cloud is sampled
+    from, unless it is cached, and then the state's geometry, unless that is
+    what was sent last. Both are built a step at a time, a few shells of the
+    table or slabs of the isosurface grid each, and the worker looks for a
+    newer request between steps as it does between chunks.
+
+    The web build has no threads, so there the frame loop does one step a
+    frame itself, and a new state never holds up a frame for longer than a
+    chunk would.
+*/
+/// what to generate: the state and how its cloud is sampled
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Request {
+    pub n: i32,
+    pub l: i32,
+    pub m: i32,
+    pub z: i32,
+    pub spin_view: SpinView,
+    pub count: usize,
+    pub sampler: Sampler,
+}
+
+impl Request {
+    pub fn wavefunction(&self) -> Wavefunction {
+        Wavefunction::new(self.n, self.l, self.m, 1. / self.z as f64)
+    }
+
+    /// n, l and m moved by the steps and kept valid: n in 1..=MAX_N, l
+    /// below n and |m| at most l
+    pub fn step(self, dn: i32, dl: i32, dm: i32) -> Request {
+        let n = (self.n + dn).clamp(1, MAX_N);
+        let l = (self.l + dl).clamp(0, n - 1);
+        let m = (self.m + dm).clamp(-l, l);
+        Request { n, l, m, ..self }
+    }
+
+    fn same_state(&self, other: &Request) -> bool {
+        (self.n, self.l, self.m, self.z) == (other.n, other.l, other.m, other.z)
+    }
+}
+
+/// what only depends on the state, not on how the cloud is sampled
+pub struct Scene {
+    pub flow: Vec<Vertex>,
+    pub surface: Isosurface,
+}
+
+pub struct Generated {
+    pub request: Request,
+    // the particles after those sent before for the same request
+    pub cloud: Vec<Particle>,
+    // the first chunk starts the cloud over, the last one completes it
+    pub first: bool,
+    pub finished: bool,
+    // only with the first chunk, when the state changed from the last
+    // request started
+    pub scene: Option<Scene>,
+}
+
+// what sampling a request needs besides the worker's CDF cache
+enum Table {
+    Cached,
+    Spin(SpinOrbital, CDFTriple),
+    Rejection(f64),
+}
+
+// a table part way built
+enum Building {
+    Cdf(CdfBuilder),
+    Spin(SpinOrbital, CdfBuilder),
+    Rejection(BoundSearch),
+}
+
+// what a job does next: its table, then its geometry, then its cloud
+enum Stage {
+    Table(Building),
+    Scene(Table, Grid),
+    Sampling(Table),
+    Done,
+}
+
+// a request being worked through a step at a time
+struct Job {
+    request: Request,
+    wavefunction: Wavefunction,
+    stage: Stage,
+    // the isosurface grid to fill once the table is built, if the state
+    // changed
+    grid: Option<Grid>,
+    scene: Option<Scene>,
+    sent: usize,
+    // chunks sampled, including any thrown away, to seed the next
+    drawn: u64,
+}
+
+// how many of a table's or grid's slices a step does
+fn per_step(slice: usize) -> usize {
+    (STEP_POINTS / slice).max(1)
+}
+
+// the real orbital, so the isosurface comes out as the textbook lobes with
+// signs
+fn amplitude(wavefunction: &Wavefunction) -> impl Fn(f64, f64, f64) -> f64 + '_ {
+    |r, theta, phi| wavefunction.wf(r, theta, phi)
+}
+
+#[derive(Default)]
+struct Worker {
+    cdfs: VecDeque<((i32, i32, i32, i32), CDFTriple)>,
+    built: Option<Request>,
+}
+
+impl Worker {
+    fn start(&mut self, request: Request) -> Job {
+        let wavefunction = request.wavefunction();
+        let grid = match self.built {
+            Some(built) if built.same_state(&request) => None,
+            _ => Some(Grid::new(wavefunction.extent(), ISO_RESO)),
+        };
+
+        // the spin views sample the spin orbital on the state's n and l
+        // instead, which has a table of its own
+        let (j, m_j) = default_spin(request.l);
+        let spin_orbital = match request.spin_view {
+            SpinView::Off => None,
+            _ => SpinOrbital::new(request.n, request.l, j, m_j, wavefunction.a0()).or_else(|| {
+                log::error!("there is no spin orbital with j = {j}/2, m_j = {m_j}/2 here");
+                None
+            }),
+        };
+        let building = if let Some(spin_orbital) = spin_orbital {
+            let builder = CdfBuilder::new(spin_orbital.extent(), CLOUD_RESO);
+            Some(Building::Spin(spin_orbital, builder))
+        } else {
+            match request.sampler {
+                Sampler::InverseCdf if self.cached(&request) => None,
+                Sampler::InverseCdf => Some(Building::Cdf(CdfBuilder::new(
+                    wavefunction.extent(),
+                    CLOUD_RESO,
+                ))),
+                Sampler::Rejection => Some(Building::Rejection(BoundSearch::new(CLOUD_RESO))),
+            }
+        };
+        let mut job = Job {
+            request,
+            wavefunction,
+            stage: Stage::Done,
+            grid,
+            scene: None,
+            sent: 0,
+            drawn: 0,
+        };
+        job.stage = match building {
+            Some(building) => Stage::Table(building),
+            None => job.built(Table::Cached),
+        };
+        job
+    }
+
+    /// does the job's next step, which hands back a chunk once there are
+    /// some to sample
+    fn step(&mut self, job: &mut Job) -> Option<Generated> {
+        let (stage, generated) = match std::mem::replace(&mut job.stage, Stage::Done) {
+            Stage::Table(building) => (self.build(job, building), None),
+            Stage::Scene(table, mut grid) => {
+                let amplitude = amplitude(&job.wavefunction);
+                // the mesh is a step of its own after the last slab
+                if !grid.full() {
+                    grid.fill(&amplitude, per_step(ISO_RESO * ISO_RESO));
+                    (Stage::Scene(table, grid), None)
+                } else {
+                    job.scene = Some(Scene {
+                        flow: flow_vertices(&job.wavefunction),
+                        surface: grid.surface(&amplitude, ISO_FRACTION),
+                    });
+                    (Stage::Sampling(table), None)
+                }
+            }
+            Stage::Sampling(mut table) => {
+                let generated = self.chunk(job, &mut table);
+                let stage = match generated.finished {
+                    true => Stage::Done,
+                    false => Stage::Sampling(table),
+                };
+                (stage, Some(generated))
+            }
+            Stage::Done => (Stage::Done, None),
+        };
+        job.stage = stage;
+        generated
+    }
+
+    // the next few slices of the job's table
+    fn build(&mut self, job: &mut Job, building: Building) -> Stage {
+        let table = match building {
+            Building::Cdf(mut builder) => {
+                let density = |r, theta, phi| job.wavefunction.density(r, theta, phi);
+                if !builder.shells(&density, per_step(builder.shell_size())) {
+                    return Stage::Table(Building::Cdf(builder));
+                }
+                self.cache(&job.request, builder.finish());
+                Table::Cached
+            }
+            Building::Spin(spin_orbital, mut builder) => {
+                let density = |r, theta, phi| spin_orbital.density(r, theta, phi);
+                if !builder.shells(&density, per_step(builder.shell_size())) {
+                    return Stage::Table(Building::Spin(spin_orbital, builder));
+                }
+                Table::Spin(spin_orbital, builder.finish())
+            }
+            Building::Rejection(mut search) => {
+                if !search.slabs(&job.wavefunction, per_step(search.slab_size())) {
+                    return Stage::Table(Building::Rejection(search));
+                }
+                Table::Rejection(search.bound())
+            }
+        };
+        job.built(table)
+    }
+
+    // the job's next chunk, each sampled with a seed of its own
+    fn chunk(&mut self, job: &mut Job, table: &mut Table) -> Generated {
+        let count = CHUNK_SIZE.min(job.request.count - job.sent);
+        let seed = job.drawn;
+        job.drawn += 1;
+        let cloud = match table {
+            Table::Cached => {
+                let cdfs = self.cdf(&job.request);
+                sample_particles(&job.wavefunction, cdfs, count, seed)
+            }
+            Table::Spin(spin_orbital, cdfs) => {
+                spinor::sample_particles(spin_orbital, cdfs, count, seed)
+            }
+            Table::Rejection(bound) => {
+                let before = *bound;
+                let cloud = sample_particles_rejection(&job.wavefunction, bound, count, seed);
+                // the chunks already sent were kept against too low a bound,
+                // so this one starts the cloud over
+                if *bound > before {
+                    job.sent = 0;
+                }
+                cloud
+            }
+        };
+        let first = job.sent == 0;
+        job.sent += count;
+        // only geometry that was sent counts as built, a job dropped before
+        // its first chunk leaves it to the next
+        let scene = job.scene.take();
+        if scene.is_some() {
+            self.built = Some(job.request);
+        }
+        Generated {
+            request: job.request,
+            cloud,
+            first,
+            finished: job.sent == job.request.count,
+            scene,
+        }
+    }
+
+    // whether the request's density has a table, which is then the newest
+    fn cached(&mut self, request: &Request) -> bool {
+        let key = cdf_key(request);
+        match self.cdfs.iter().position(|(k, _)| *k == key) {
+            Some(i) => {
+                let entry = self.cdfs.remove(i).unwrap();
+                self.cdfs.push_back(entry);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn cache(&mut self, request: &Request, cdfs: CDFTriple) {
+        if self.cdfs.len() == CDF_CACHE_SIZE {
+            self.cdfs.pop_front();
+        }
+        self.cdfs.push_back((cdf_key(request), cdfs));
+    }
+
+    fn cdf(&self, request: &Request) -> &CDFTriple {
+        let key = cdf_key(request);
+        let (_, cdfs) = self.cdfs.iter().find(|(k, _)| *k == key).unwrap();
+        cdfs
+    }
+}
+
+impl Job {
+    // the stage after the table, the geometry if it's wanted
+    fn built(&mut self, table: Table) -> Stage {
+        match self.grid.take() {
+            Some(grid) => Stage::Scene(table, grid),
+            None => Stage::Sampling(table),
+        }
+    }
+}
+
+/// j and m_j, doubled, of the spin orbital shown for a state with this l.
+/// j = l - 1/2 doesn't exist for s states, so it's j = l + 1/2 with m_j = j.
+pub fn default_spin(l: i32) -> (i32, i32) {
+    (2 * l + 1, 2 * l + 1)
+}
+
+// |ψ|² doesn't depend on the sign of m
+fn cdf_key(request: &Request) -> (i32, i32, i32, i32) {
+    (request.n, request.l, request.m.abs(), request.z)
+}
+
+fn flow_vertices(wavefunction: &Wavefunction) -> Vec<Vertex> {
+    let samples = gen_current_field(wavefunction, FLOW_RESO, FLOW_CUTOFF);
+    let step = 2. * wavefunction.extent() / (FLOW_RESO - 1) as f64;
+    flow::arrow_vertices(&samples, 0.9 * step as f32)
+}
+
+/// hands requests to the worker and results back, in order
+pub struct Generator {
+    #[cfg(not(target_arch = "wasm32"))]
+    requests: std::sync::mpsc::Sender<(u64, Request)>,
+    #[cfg(not(target_arch = "wasm32"))]
+    results: std::sync::mpsc::Receiver<(u64, Generated)>,
+    #[cfg(target_arch = "wasm32")]
+    worker: Worker,
+    #[cfg(target_arch = "wasm32")]
+    pending: Option<(u64, Request)>,
+    #[cfg(target_arch = "wasm32")]
+    job: Option<(u64, Job)>,
+    #[cfg(target_arch = "wasm32")]
+    ready: VecDeque<(u64, Generated)>,
+    // numbers of the newest request and of the newest result handed back,
+    // and whether that result was its request's last
+    requested: u64,
+    received: u64,
+    finished: bool,
+}
+
+impl Generator {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new() -> Generator {
+        use std::sync::mpsc;
+
+        let (requests, inbox) = mpsc::channel::<(u64, Request)>();
+        let (outbox, results) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut worker = Worker::default();
+            let mut next = inbox.recv().ok();
+            while let Some(mut request) = next.take() {
+                // only the newest request matters, the rest were stepped past
+                while let Ok(newer) = inbox.try_recv() {
+                    request = newer;
+                }
+                let (number, request) = request;
+                let mut job = worker.start(request);
+                loop {
+                    if let Some(generated) = worker.step(&mut job) {
+                        let finished = generated.finished;
+                        if outbox.send((number, generated)).is_err() {
+                            return;
+                        }
+                        if finished {
+                            break;
+                        }
+                    }
+                    // a newer request cancels the rest of this one
+                    if let Ok(newer) = inbox.try_recv() {
+                        next = Some(newer);
+                        break;
+                    }
+                }
+                if next.is_none() {
+                    next = inbox.recv().ok();
+                }
+            }
+        });
+        Generator {
+            requests,
+            results,
+            requested: 0,
+            received: 0,
+            finished: true,
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn new() -> Generator {
+        Generator {
+            worker: Worker::default(),
+            pending: None,
+            job: None,
+            ready: VecDeque::new(),
+            requested: 0,
+            received: 0,
+            finished: true,
+        }
+    }
+
+    pub fn request(&mut self, request: Request) {
+        self.requested += 1;
+        #[cfg(not(target_arch = "wasm32"))]
+        self.requests
+            .send((self.requested, request))
+            .expect("the generator thread stopped");
+        // started on the next frame, so several requests in one frame only
+        // cost the newest
+        #[cfg(target_arch = "wasm32")]
+        {
+            self.pending = Some((self.requested, request));
+        }
+    }
+
+    /// on the web, does the next step of work in place of a thread.
+    /// Natively the thread does it and this does nothing.
+    pub fn work(&mut self) {
+        #[cfg(target_arch = "wasm32")]
+        {
+            if let Some((number, request)) = self.pending.take() {
+                self.job = Some((number, self.worker.start(request)));
+            }
+            if let Some((number, mut job)) = self.job.take() {
+                match self.worker.step(&mut job) {
+                    Some(generated) => {
+                        if !generated.finished {
+                            self.job = Some((number, job));
+                        }
+                        self.ready.push_back((number, generated));
+                    }
+                    None => self.job = Some((number, job)),
+                }
+            }
+        }
+    }
+
+    /// the next result if there is one, without waiting
+    pub fn poll(&mut self) -> Option<Generated> {
+        loop {
+            #[cfg(not(target_arch = "wasm32"))]
+            let next = self.results.try_recv().ok()?;
+            #[cfg(target_arch = "wasm32")]
+            let next = self.ready.pop_front()?;
+            if let Some(generated) = self.accept(next) {
+                return Some(generated);
+            }
+        }
+    }
+
+    /// the next result, waiting for it if needed
+    pub fn wait(&mut self) -> Generated {
+        loop {
+            #[cfg(not(target_arch = "wasm32"))]
+            let next = self.results.recv().expect("the generator thread stopped");
+            #[cfg(target_arch = "wasm32")]
+            let next = loop {
+                if let Some(next) = self.ready.pop_front() {
+                    break next;
+                }
+                assert!(
+                    self.job.is_some() || self.pending.is_some(),
+                    "nothing was requested"
+                );
+                self.work();
+            };
+            if let Some(generated) = self.accept(next) {
+                return generated;
+            }
+        }
+    }
+
+    // chunks of requests that have been replaced are dropped, unless they
+    // bring a new state that later chunks won't
+    fn accept(&mut self, (number, generated): (u64, Generated)) -> Option<Generated> {
+        if number != self.requested && generated.scene.is_none() {
+            return None;
+        }
+        self.received = number;
+        self.finished = generated.finished;
+        Some(generated)
+    }
+
+    /// whether the newest request is still being worked on
+    pub fn busy(&self) -> bool {
+        self.received != self.requested || !self.finished
+    }
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Building, CHUNK_SIZE, Generated, Job, MAX_N, Request, Stage, Worker};
+    use crate::graphics::cloud::{Sampler, SpinView};
+
+    // steps until the next chunk, counting the steps it took
+    fn next_chunk(worker: &mut Worker, job: &mut Job) -> (Generated, usize) {
+        let mut steps = 1;
+        loop {
+            if let Some(generated) = worker.step(job) {
+                return (generated, steps);
+            }
+            steps += 1;
+        }
+    }
+
+    #[test]
+    fn test_step_and_cache() {
+        let request = Request {
+            n: 2,
+            l: 1,
+            m: 1,
+            z: 1,
+            spin_view: SpinView::Off,
+            count: 100,
+            sampler: Sampler::InverseCdf,
+        };
+        // l and m are pulled in when n drops below them
+        let lower = request.step(-1, 0, 0);
+        assert_eq!((lower.n, lower.l, lower.m), (1, 0, 0));
+        let top = request.step(100, 100, 100);
+        assert_eq!((top.n, top.l, top.m), (MAX_N, MAX_N - 1, MAX_N - 1));
+        let bottom = top.step(0, 0, -100);
+        assert_eq!(bottom.m, 1 - MAX_N);
+
+        // a new state is a table and geometry built over several steps
+        // before the first chunk, which brings the geometry
+        let mut worker = Worker::default();
+        let mut job = worker.start(request);
+        let (first, steps) = next_chunk(&mut worker, &mut job);
+        assert!(steps > 4 && first.scene.is_some() && first.finished);
+        assert_eq!(worker.cdfs.len(), 1);
+
+        // m and -m share a density, and so a table, and sampling it again
+        // doesn't rebuild the geometry
+        let job = worker.start(request.step(0, 0, -2));
+        assert!(matches!(job.stage, Stage::Scene(..)));
+        let mut job = worker.start(request);
+        assert!(matches!(job.stage, Stage::Sampling(_)));
+        let (again, steps) = next_chunk(&mut worker, &mut job);
+        assert!(steps == 1 && again.scene.is_none());
+        assert_eq!(worker.cdfs.len(), 1);
+
+        // a cloud comes in chunks, the geometry only with the first
+        let big = Request {
+            count: CHUNK_SIZE + 10,
+            ..lower
+        };
+        let mut job = worker.start(big);
+        let (first, _) = next_chunk(&mut worker, &mut job);
+        assert!(first.first && !first.finished && first.scene.is_some());
+        assert_eq!(first.cloud.len(), CHUNK_SIZE);
+        let (last, steps) = next_chunk(&mut worker, &mut job);
+        assert!(!last.first && last.finished && last.scene.is_none() && steps == 1);
+        assert_eq!(last.cloud.len(), 10);
+        assert_ne!(first.cloud[0].position, last.cloud[0].position);
+        assert_eq!(worker.cdfs.len(), 2);
+        assert!(worker.step(&mut job).is_none());
+    }
+
+    #[test]
+    fn test_spin_view() {
+        // every l has a spin orbital to show, d states included
+        let mut worker = Worker::default();
+        for l in 0..3 {
+            let request = Request {
+                n: 3,
+                l,
+                m: 0,
+                z: 1,
+                spin_view: SpinView::Separate,
+                count: 100,
+                sampler: Sampler::InverseCdf,
+            };
+            let mut job = worker.start(request);
+            assert!(matches!(job.stage, Stage::Table(Building::Spin(..))));
+            let (generated, _) = next_chunk(&mut worker, &mut job);
+            assert!(generated.finished);
+            assert!(generated.cloud.iter().all(|p| p.spin.is_some()));
+        }
+    }
+}
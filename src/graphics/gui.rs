@@ -0,0 +1,347 @@
+use super::text::TextBatch;
+use std::ops::RangeInclusive;
+
+/* NOTE:
+    An immediate mode panel: every frame the widgets are declared again with
+    the current values, and each one draws itself into the text batch and
+    reports whether the mouse changed it. Nothing about a widget is kept
+    between frames except which slider is being dragged and which dropdown is
+    open, and those are known by the order the widgets are declared in.
+
+    While a dropdown is open it owns the mouse, the next click either picks
+    one of its options or closes it. Its list is drawn last so it covers the
+    widgets below it.
+*/
+
+// characters of label in front of every widget, and of the whole panel
+const LABEL_COLUMNS: usize = 10;
+const PANEL_COLUMNS: usize = 32;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Style {
+    pub text: [f32; 4],
+    pub panel: [f32; 4],
+    pub field: [f32; 4],
+    pub accent: [f32; 4],
+}
+
+type Rect = ([f32; 2], [f32; 2]);
+
+fn contains((min, max): Rect, p: [f32; 2]) -> bool {
+    p[0] >= min[0] && p[0] < max[0] && p[1] >= min[1] && p[1] < max[1]
+}
+
+/// the mouse as the panel sees it, and what it remembers between frames
+#[derive(Default)]
+pub struct Gui {
+    cursor: [f32; 2],
+    down: bool,
+    // a press no frame has handled yet
+    clicked: bool,
+    dragging: Option<usize>,
+    open: Option<usize>,
+    // where the panel and any open list were drawn last frame
+    area: Vec<Rect>,
+}
+
+impl Gui {
+    pub fn cursor_moved(&mut self, x: f64, y: f64) {
+        self.cursor = [x as f32, y as f32];
+    }
+
+    /// whether the panel takes the press, otherwise it is the camera's
+    pub fn mouse_button(&mut self, pressed: bool) -> bool {
+        self.down = pressed;
+        if !pressed {
+            return false;
+        }
+        let taken = self.open.is_some() || self.area.iter().any(|r| contains(*r, self.cursor));
+        self.clicked |= taken;
+        taken
+    }
+
+    /// lays the panel out from its top left corner
+    pub fn begin<'a>(&'a mut self, batch: &'a mut TextBatch, at: [f32; 2], style: Style) -> Ui<'a> {
+        let [char_width, line_height] = batch.measure("M");
+        let pad = (line_height / 4.).round();
+        self.area.clear();
+        Ui {
+            start: batch.vertices.len(),
+            gui: self,
+            batch,
+            style,
+            at,
+            y: at[1],
+            width: PANEL_COLUMNS as f32 * char_width,
+            label_width: LABEL_COLUMNS as f32 * char_width,
+            row_height: line_height + 2. * pad,
+            pad,
+            next_id: 0,
+            list: None,
+        }
+    }
+}
+
+// the open dropdown's options and the box they hang under
+struct List {
+    options: Vec<String>,
+    selected: usize,
+    rect: Rect,
+}
+
+/// one frame of the panel, widgets go top to bottom in declaration order
+pub struct Ui<'a> {
+    gui: &'a mut Gui,
+    batch: &'a mut TextBatch,
+    style: Style,
+    at: [f32; 2],
+    y: f32,
+    width: f32,
+    label_width: f32,
+    row_height: f32,
+    pad: f32,
+    // where the panel's vertices start, its background goes in front of them
+    start: usize,
+    next_id: usize,
+    list: Option<List>,
+}
+
+impl Ui<'_> {
+    // the next row's id, label, and the field to the right of the label
+    fn row(&mut self, label: &str) -> (usize, Rect) {
+        let id = self.next_id;
+        self.next_id += 1;
+        let [x, y] = [self.at[0], self.y];
+        self.batch.text(label, [x, y + self.pad], self.style.text);
+        self.y += self.row_height + self.pad;
+        (
+            id,
+            (
+                [x + self.label_width, y],
+                [x + self.width, y + self.row_height],
+            ),
+        )
+    }
+
+    // a click on `rect` this frame, taken so nothing else sees it
+    fn click(&mut self, rect: Rect) -> bool {
+        let hit = self.gui.clicked && self.gui.open.is_none() && contains(rect, self.gui.cursor);
+        if hit {
+            self.gui.clicked = false;
+        }
+        hit
+    }
+
+    /// a bar filled to `t` in 0..=1 showing `value`, the new t while it is
+    /// dragged
+    pub fn slider(&mut self, label: &str, value: &str, t: f32) -> Option<f32> {
+        let (id, rect) = self.row(label);
+        if self.click(rect) {
+            self.gui.dragging = Some(id);
+        }
+        let dragged = (self.gui.dragging == Some(id) && self.gui.down).then(|| {
+            let ([x0, _], [x1, _]) = rect;
+            ((self.gui.cursor[0] - x0) / (x1 - x0)).clamp(0., 1.)
+        });
+
+        let (min, max) = rect;
+        let fill = min[0] + (max[0] - min[0]) * dragged.unwrap_or(t).clamp(0., 1.);
+        self.batch.rect(min, max, self.style.field);
+        self.batch.rect(min, [fill, max[1]], self.style.accent);
+        self.batch.text(
+            value,
+            [min[0] + self.pad, min[1] + self.pad],
+            self.style.text,
+        );
+        dragged
+    }
+
+    /// a whole number slider, true when it changed `value`
+    pub fn int_slider(&mut self, label: &str, value: &mut i32, range: RangeInclusive<i32>) -> bool {
+        let (lo, hi) = (*range.start(), *range.end());
+        let t = if hi > lo {
+            (*value - lo) as f32 / (hi - lo) as f32
+        } else {
+            1.
+        };
+        let Some(t) = self.slider(label, &value.to_string(), t) else {
+            return false;
+        };
+        let new = lo + (t * (hi - lo) as f32).round() as i32;
+        let changed = new != *value;
+        *value = new;
+        changed
+    }
+
+    /// a slider over `range`, shown with `decimals` places
+    pub fn float_slider(
+        &mut self,
+        label: &str,
+        value: &mut f32,
+        range: RangeInclusive<f32>,
+        decimals: usize,
+    ) -> bool {
+        let (lo, hi) = (*range.start(), *range.end());
+        let text = format!("{:.*}", decimals, value);
+        let Some(t) = self.slider(label, &text, (*value - lo) / (hi - lo)) else {
+            return false;
+        };
+        let new = lo + t * (hi - lo);
+        let changed = new != *value;
+        *value = new;
+        changed
+    }
+
+    /// a box showing the selected option, clicking it lists the others.
+    /// True when a different option was picked.
+    pub fn dropdown(&mut self, label: &str, selected: &mut usize, options: &[&str]) -> bool {
+        let (id, rect) = self.row(label);
+        let (min, max) = rect;
+        self.batch.rect(min, max, self.style.field);
+        self.batch.text(
+            options.get(*selected).copied().unwrap_or_default(),
+            [min[0] + self.pad, min[1] + self.pad],
+            self.style.text,
+        );
+        let [arrow, _] = self.batch.measure("v");
+        self.batch.text(
+            "v",
+            [max[0] - arrow - self.pad, min[1] + self.pad],
+            self.style.text,
+        );
+
+        if self.gui.open != Some(id) {
+            if self.click(rect) {
+                self.gui.open = Some(id);
+            } else {
+                return false;
+            }
+        } else if self.gui.clicked {
+            // the list owns this click, wherever it landed
+            self.gui.clicked = false;
+            self.gui.open = None;
+            let row = ((self.gui.cursor[1] - max[1]) / self.row_height).floor();
+            let inside = self.gui.cursor[0] >= min[0] && self.gui.cursor[0] < max[0];
+            if inside && row >= 0. && (row as usize) < options.len() {
+                let picked = row as usize;
+                let changed = picked != *selected;
+                *selected = picked;
+                return changed;
+            }
+            return false;
+        }
+        self.list = Some(List {
+            options: options.iter().map(|o| o.to_string()).collect(),
+            selected: *selected,
+            rect,
+        });
+        false
+    }
+
+    /// draws the background and any open list, and returns the corners of
+    /// the panel
+    pub fn end(self) -> ([f32; 2], [f32; 2]) {
+        let min = [self.at[0] - self.pad, self.at[1] - self.pad];
+        let max = [self.at[0] + self.width + self.pad, self.y];
+        self.batch
+            .rect_behind(self.start, min, max, self.style.panel);
+        self.gui.area.push((min, max));
+
+        if let Some(list) = self.list {
+            let (box_min, box_max) = list.rect;
+            let bottom = box_max[1] + list.options.len() as f32 * self.row_height;
+            self.batch.rect(
+                box_min.map(|c| c - 1.),
+                [box_max[0] + 1., bottom + 1.],
+                self.style.text,
+            );
+            for (i, option) in list.options.iter().enumerate() {
+                let top = box_max[1] + i as f32 * self.row_height;
+                let color = if i == list.selected {
+                    self.style.accent
+                } else {
+                    self.style.field
+                };
+                self.batch.rect(
+                    [box_min[0], top],
+                    [box_max[0], top + self.row_height],
+                    color,
+                );
+                self.batch.text(
+                    option,
+                    [box_min[0] + self.pad, top + self.pad],
+                    self.style.text,
+                );
+            }
+            self.gui.area.push((box_min, [box_max[0], bottom]));
+        }
+
+        // a click that missed every widget is dropped, and a drag ends with
+        // the button
+        self.gui.clicked = false;
+        if !self.gui.down {
+            self.gui.dragging = None;
+        }
+        (min, max)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Gui, Style};
+    use crate::graphics::text::TextBatch;
+
+    #[test]
+    fn test_widgets() {
+        let style = Style {
+            text: [0.; 4],
+            panel: [1.; 4],
+            field: [0.5; 4],
+            accent: [0.; 4],
+        };
+        let batch = || TextBatch::new(winit::dpi::PhysicalSize::new(800, 600), 1);
+        let mut gui = Gui::default();
+        let options = ["mesh", "billboard", "volume"];
+        let mut n = 2;
+        let mut mode = 0;
+
+        // nothing is drawn yet, so a press anywhere is the camera's
+        gui.cursor_moved(100., 10.);
+        assert!(!gui.mouse_button(true));
+        gui.mouse_button(false);
+        let frame = |gui: &mut Gui, n: &mut i32, mode: &mut usize| {
+            let mut batch = batch();
+            let mut ui = gui.begin(&mut batch, [0., 0.], style);
+            let slid = ui.int_slider("n", n, 1..=5);
+            let picked = ui.dropdown("mode", mode, &options);
+            ui.end();
+            (slid, picked)
+        };
+        assert_eq!(frame(&mut gui, &mut n, &mut mode), (false, false));
+
+        // the slider's field runs from the label to the panel's edge, 60 to
+        // 192 pixels at scale 1, and rows are 12 high with 2 between them
+        gui.cursor_moved(191., 5.);
+        assert!(gui.mouse_button(true));
+        assert_eq!(frame(&mut gui, &mut n, &mut mode), (true, false));
+        assert_eq!(n, 5);
+        // it keeps following the cursor off the field until released
+        gui.cursor_moved(0., 300.);
+        assert_eq!(frame(&mut gui, &mut n, &mut mode), (true, false));
+        assert_eq!(n, 1);
+        gui.mouse_button(false);
+        gui.cursor_moved(192., 5.);
+        assert_eq!(frame(&mut gui, &mut n, &mut mode), (false, false));
+
+        // open the dropdown on the second row, then pick its last option
+        gui.cursor_moved(100., 20.);
+        assert!(gui.mouse_button(true));
+        gui.mouse_button(false);
+        assert_eq!(frame(&mut gui, &mut n, &mut mode), (false, false));
+        gui.cursor_moved(100., 26. + 12. * 2.5);
+        assert!(gui.mouse_button(true));
+        gui.mouse_button(false);
+        assert_eq!(frame(&mut gui, &mut n, &mut mode), (false, true));
+        assert_eq!(mode, 2);
+    }
+}
@@ -0,0 +1,126 @@
+use crate::particle_gen::wavefunction::Wavefunction;
+
+// weight of the newest frame in the smoothed frame time
+const FRAME_SMOOTHING: f64 = 0.05;
+
+pub const KEY_HELP: &str = "\
+W S        zoom in, out
+A D Q E    orbit
+drag       orbit
+right drag pan
+wheel      zoom
+1 finger   orbit
+2 fingers  zoom, pan
+R          reset camera
+Z          free fly
+O          orthographic
+F1 - F4    view +x +y +z iso
+J          turntable
+Y          play camera path
+0 - 9      recall view
+Shift 0-9  bookmark view
+Up Down    step n
+Left Right step l
+PgUp PgDn  step m
+M          render mode
+V P        colour map, gradient
+T          spin view
+F          flow arrows
+I          isosurface
+K L        add, clear clip plane
+, .        move clip plane
+H          density slice
+[ ]        volume opacity
+- =        volume contrast
+X G N      axes, grid, nucleus
+B U        scale bar, unit
+C          export charts
+Tab        control panel
+/          hide keys";
+
+/// time between frames, smoothed so the readout doesn't flicker
+pub struct FrameTimer {
+    last: instant::Instant,
+    delta: f64,
+    seconds: f64,
+}
+
+impl FrameTimer {
+    pub fn new() -> FrameTimer {
+        FrameTimer {
+            last: instant::Instant::now(),
+            delta: 0.,
+            seconds: 0.,
+        }
+    }
+
+    pub fn tick(&mut self) {
+        let now = instant::Instant::now();
+        let dt = (now - self.last).as_secs_f64();
+        self.last = now;
+        self.delta = dt;
+        self.seconds = if self.seconds == 0. {
+            dt
+        } else {
+            self.seconds + (dt - self.seconds) * FRAME_SMOOTHING
+        };
+    }
+
+    pub fn seconds(&self) -> f64 {
+        self.seconds
+    }
+
+    /// the last frame's time as it was, for moving things by
+    pub fn delta(&self) -> f64 {
+        self.delta
+    }
+}
+
+impl Default for FrameTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// the state, its energy, how many particles it is drawn with and how long
+/// a frame takes. `spin` is j and m_j doubled when the spin orbital is shown.
+pub fn status(
+    wavefunction: &Wavefunction,
+    spin: Option<(i32, i32)>,
+    particles: usize,
+    frame_seconds: f64,
+) -> String {
+    let (n, l, m) = (wavefunction.n(), wavefunction.l(), wavefunction.m());
+    let state = match spin {
+        None => format!("ψ(n, l, m) = ({n}, {l}, {m})"),
+        Some((j, m_j)) => format!("ψ(n, l, j, m_j) = ({n}, {l}, {j}/2, {m_j}/2)"),
+    };
+    let fps = if frame_seconds > 0. {
+        1. / frame_seconds
+    } else {
+        0.
+    };
+    format!(
+        "{state}\nE = {:.3} eV\n{particles} particles\n{:.1} ms ({fps:.0} fps)",
+        wavefunction.energy(),
+        frame_seconds * 1000.,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::status;
+    use crate::particle_gen::wavefunction::Wavefunction;
+
+    #[test]
+    fn test_status() {
+        let text = status(&Wavefunction::new(2, 1, 1, 1.), None, 20000, 0.02);
+        assert_eq!(
+            text,
+            "ψ(n, l, m) = (2, 1, 1)\nE = -3.401 eV\n20000 particles\n20.0 ms (50 fps)"
+        );
+        // halving a0 is doubling Z, which quadruples the binding
+        let text = status(&Wavefunction::new(1, 0, 0, 0.5), Some((1, -1)), 10, 0.);
+        assert!(text.starts_with("ψ(n, l, j, m_j) = (1, 0, 1/2, -1/2)\nE = -54.423 eV"));
+    }
+}
@@ -2,51 +2,51 @@ use super::vertex::Vertex;
 
 pub const VERTICES: &[Vertex] = &[
     Vertex {
-        position: [0.5, 0.8090169943749475, 0.],
+        position: [0.5, 0.809_017, 0.],
         color: [0.5, 0.5, 1.0],
     },
     Vertex {
-        position: [-0.5, 0.8090169943749475, 0.],
+        position: [-0.5, 0.809_017, 0.],
         color: [0.5, 0.5, 1.0],
     },
     Vertex {
-        position: [0.5, -0.8090169943749475, 0.],
+        position: [0.5, -0.809_017, 0.],
         color: [0.5, 0.5, 1.0],
     },
     Vertex {
-        position: [-0.5, -0.8090169943749475, 0.],
+        position: [-0.5, -0.809_017, 0.],
         color: [0.5, 0.5, 1.0],
     },
     Vertex {
-        position: [0., 0.5, 0.8090169943749475],
+        position: [0., 0.5, 0.809_017],
         color: [0.5, 0.5, 1.0],
     },
     Vertex {
-        position: [0., -0.5, 0.809016994374947],
+        position: [0., -0.5, 0.809_017],
         color: [0.5, 0.5, 1.0],
     },
     Vertex {
-        position: [0., 0.5, -0.8090169943749475],
+        position: [0., 0.5, -0.809_017],
         color: [0.5, 0.5, 1.0],
     },
     Vertex {
-        position: [0., -0.5, -0.8090169943749475],
+        position: [0., -0.5, -0.809_017],
         color: [0.5, 0.5, 1.0],
     },
     Vertex {
-        position: [0.8090169943749475, 0., 0.5],
+        position: [0.809_017, 0., 0.5],
         color: [0.5, 0.5, 1.0],
     },
     Vertex {
-        position: [0.809016994374947, 0., -0.5],
+        position: [0.809_017, 0., -0.5],
         color: [0.5, 0.5, 1.0],
     },
     Vertex {
-        position: [-0.8090169943749475, 0., 0.5],
+        position: [-0.809_017, 0., 0.5],
         color: [0.5, 0.5, 1.0],
     },
     Vertex {
-        position: [-0.8090169943749475, 0., -0.5],
+        position: [-0.809_017, 0., -0.5],
         color: [0.5, 0.5, 1.0],
     },
 ];
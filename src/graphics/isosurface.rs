@@ -0,0 +1,277 @@
+use super::colormap::sign_color;
+use super::vertex::SurfaceVertex;
+use crate::particle_gen::cartesian_to_spherical;
+
+/* NOTE:
+    The surface is extracted with surface nets, the simplest kind of dual
+    contouring. |ψ|² is sampled on a cube of grid points, and every cell the
+    surface passes through gets one vertex, the average of where the surface
+    cuts the cell's edges. Every grid edge the surface crosses is shared by
+    four cells, and their four vertices make a quad. Compared to marching
+    cubes this needs no case tables and gives fewer, better shaped triangles.
+
+    The level is picked so the surface encloses `fraction` of the probability:
+    the grid points are sorted by density and summed densest first until the
+    fraction is reached, which is exactly the region above the level.
+*/
+pub struct Isosurface {
+    pub vertices: Vec<SurfaceVertex>,
+    pub indices: Vec<u32>,
+    pub level: f64,
+}
+
+/// `amplitude` is a real ψ(r, θ, φ), the density is its square and the
+/// surface is coloured by its sign
+pub fn extract<F>(amplitude: F, extent: f64, reso: usize, fraction: f64) -> Isosurface
+where
+    F: Fn(f64, f64, f64) -> f64,
+{
+    let mut grid = Grid::new(extent, reso);
+    while !grid.fill(&amplitude, reso) {}
+    grid.surface(&amplitude, fraction)
+}
+
+/// the density samples `extract` works from, which can be filled a few
+/// slabs at a time
+pub struct Grid {
+    extent: f64,
+    reso: usize,
+    density: Vec<f64>,
+    // slabs of constant x filled so far
+    filled: usize,
+}
+
+impl Grid {
+    pub fn new(extent: f64, reso: usize) -> Grid {
+        Grid {
+            extent,
+            reso,
+            density: Vec::with_capacity(reso * reso * reso),
+            filled: 0,
+        }
+    }
+
+    /// samples the next `slabs` slabs, and says whether the grid is full
+    pub fn fill<F>(&mut self, amplitude: &F, slabs: usize) -> bool
+    where
+        F: Fn(f64, f64, f64) -> f64,
+    {
+        let (extent, reso) = (self.extent, self.reso);
+        let step = 2. * extent / (reso - 1) as f64;
+        let coord = |i: usize| i as f64 * step - extent;
+        let end = (self.filled + slabs).min(reso);
+        for i in self.filled..end {
+            for j in 0..reso {
+                for k in 0..reso {
+                    let (r, theta, phi) = cartesian_to_spherical(coord(i), coord(j), coord(k));
+                    self.density.push(amplitude(r, theta, phi).powi(2));
+                }
+            }
+        }
+        self.filled = end;
+        self.full()
+    }
+
+    pub fn full(&self) -> bool {
+        self.filled == self.reso
+    }
+
+    /// the surface through a full grid
+    pub fn surface<F>(&self, amplitude: &F, fraction: f64) -> Isosurface
+    where
+        F: Fn(f64, f64, f64) -> f64,
+    {
+        assert!(self.full(), "the grid isn't full yet");
+        mesh(amplitude, self.extent, self.reso, &self.density, fraction)
+    }
+}
+
+fn mesh<F>(amplitude: &F, extent: f64, reso: usize, density: &[f64], fraction: f64) -> Isosurface
+where
+    F: Fn(f64, f64, f64) -> f64,
+{
+    let step = 2. * extent / (reso - 1) as f64;
+    let coord = |i: usize| i as f64 * step - extent;
+    let index = |i: usize, j: usize, k: usize| (i * reso + j) * reso + k;
+    let field = |x: f64, y: f64, z: f64| {
+        let (r, theta, phi) = cartesian_to_spherical(x, y, z);
+        amplitude(r, theta, phi)
+    };
+    let level = enclosing_level(density, fraction);
+
+    // one vertex per cell the surface passes through
+    let cells = reso - 1;
+    let cell_index = |i: usize, j: usize, k: usize| (i * cells + j) * cells + k;
+    let mut cell_vertex = vec![u32::MAX; cells * cells * cells];
+    let mut vertices = Vec::new();
+    for i in 0..cells {
+        for j in 0..cells {
+            for k in 0..cells {
+                let corner = |c: usize| {
+                    let (di, dj, dk) = (c >> 2 & 1, c >> 1 & 1, c & 1);
+                    let d = density[index(i + di, j + dj, k + dk)] - level;
+                    ([di as f64, dj as f64, dk as f64], d)
+                };
+                let mut sum = [0.; 3];
+                let mut crossings = 0;
+                for (a, b) in CELL_EDGES {
+                    let ((pa, da), (pb, db)) = (corner(a), corner(b));
+                    if (da > 0.) == (db > 0.) {
+                        continue;
+                    }
+                    let t = da / (da - db);
+                    for axis in 0..3 {
+                        sum[axis] += pa[axis] + (pb[axis] - pa[axis]) * t;
+                    }
+                    crossings += 1;
+                }
+                if crossings == 0 {
+                    continue;
+                }
+                let local = sum.map(|s| s / crossings as f64);
+                let position = [
+                    coord(i) + local[0] * step,
+                    coord(j) + local[1] * step,
+                    coord(k) + local[2] * step,
+                ];
+                cell_vertex[cell_index(i, j, k)] = vertices.len() as u32;
+                vertices.push(surface_vertex(&field, position, step));
+            }
+        }
+    }
+
+    // a quad around every grid edge that crosses the level, wound so it faces
+    // out of the enclosed region
+    let mut indices = Vec::new();
+    for i in 0..reso {
+        for j in 0..reso {
+            for k in 0..reso {
+                let p = [i, j, k];
+                let inside = density[index(i, j, k)] > level;
+                for axis in 0..3 {
+                    let (b, c) = ((axis + 1) % 3, (axis + 2) % 3);
+                    // the four cells around the edge have to exist
+                    if p[axis] + 1 >= reso
+                        || p[b] == 0
+                        || p[c] == 0
+                        || p[b] >= cells
+                        || p[c] >= cells
+                    {
+                        continue;
+                    }
+                    let mut q = p;
+                    q[axis] += 1;
+                    if inside == (density[index(q[0], q[1], q[2])] > level) {
+                        continue;
+                    }
+                    let quad = [(0, 0), (1, 0), (1, 1), (0, 1)].map(|(db, dc)| {
+                        let mut cell = p;
+                        cell[b] = cell[b] + db - 1;
+                        cell[c] = cell[c] + dc - 1;
+                        cell_vertex[cell_index(cell[0], cell[1], cell[2])]
+                    });
+                    let [v0, v1, v2, v3] = if inside {
+                        quad
+                    } else {
+                        [quad[0], quad[3], quad[2], quad[1]]
+                    };
+                    indices.extend_from_slice(&[v0, v1, v2, v0, v2, v3]);
+                }
+            }
+        }
+    }
+
+    Isosurface {
+        vertices,
+        indices,
+        level,
+    }
+}
+
+// corner pairs of a cell's twelve edges, corners numbered by bits (x, y, z)
+const CELL_EDGES: [(usize, usize); 12] = [
+    (0b000, 0b100),
+    (0b010, 0b110),
+    (0b001, 0b101),
+    (0b011, 0b111),
+    (0b000, 0b010),
+    (0b100, 0b110),
+    (0b001, 0b011),
+    (0b101, 0b111),
+    (0b000, 0b001),
+    (0b100, 0b101),
+    (0b010, 0b011),
+    (0b110, 0b111),
+];
+
+// the density level above which `fraction` of the sampled probability lies
+fn enclosing_level(density: &[f64], fraction: f64) -> f64 {
+    let mut sorted = density.to_vec();
+    sorted.sort_by(|a, b| b.total_cmp(a));
+    let total: f64 = sorted.iter().sum();
+    let mut enclosed = 0.;
+    for d in &sorted {
+        enclosed += d;
+        if enclosed >= fraction * total {
+            return *d;
+        }
+    }
+    0.
+}
+
+// the normal is the density gradient pointing downhill, which is out of the
+// surface. It is taken from the field itself rather than the grid so the
+// shading stays smooth.
+fn surface_vertex<F>(field: &F, position: [f64; 3], step: f64) -> SurfaceVertex
+where
+    F: Fn(f64, f64, f64) -> f64,
+{
+    let [x, y, z] = position;
+    let h = step * 0.5;
+    let density = |x: f64, y: f64, z: f64| field(x, y, z).powi(2);
+    let gradient = [
+        density(x + h, y, z) - density(x - h, y, z),
+        density(x, y + h, z) - density(x, y - h, z),
+        density(x, y, z + h) - density(x, y, z - h),
+    ];
+    let norm = gradient.iter().map(|g| g * g).sum::<f64>().sqrt();
+    let normal = if norm > 0. {
+        gradient.map(|g| (-g / norm) as f32)
+    } else {
+        [0., 0., 1.]
+    };
+    SurfaceVertex {
+        position: position.map(|p| p as f32),
+        normal,
+        color: sign_color(field(x, y, z)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::extract;
+    use crate::particle_gen::wavefunction::Wavefunction;
+
+    #[test]
+    fn test_enclosing_surface() {
+        // 90% of the 1s probability lies within r = 2.66 a0
+        let state = Wavefunction::new(1, 0, 0, 1.);
+        let surface = extract(|r, t, p| state.wf(r, t, p), 6., 48, 0.9);
+        assert!(!surface.indices.is_empty());
+        for v in &surface.vertices {
+            let [x, y, z] = v.position;
+            let r = (x * x + y * y + z * z).sqrt();
+            assert!((r - 2.66).abs() < 0.15, "{r}");
+            // normals point away from the nucleus
+            let outward = (v.normal[0] * x + v.normal[1] * y + v.normal[2] * z) / r;
+            assert!(outward > 0.95);
+        }
+
+        // the triangles face out too
+        let p = |i: u32| cgmath::Vector3::from(surface.vertices[i as usize].position);
+        for tri in surface.indices.chunks(3) {
+            let normal = (p(tri[1]) - p(tri[0])).cross(p(tri[2]) - p(tri[0]));
+            assert!(cgmath::dot(normal, p(tri[0])) > 0.);
+        }
+    }
+}
@@ -1,4 +1,23 @@
+pub mod bookmark;
 pub mod camera;
+pub mod cloud;
+pub mod colormap;
+pub mod depth;
+pub mod flow;
+pub mod font;
+pub mod generate;
+pub mod gui;
+pub mod hud;
 pub mod icosahedron;
+pub mod instance;
+pub mod isosurface;
+pub mod morph;
+pub mod path;
+pub mod reference;
 pub mod render;
+pub mod rows;
+pub mod slice;
+pub mod text;
+pub mod touch;
 pub mod vertex;
+pub mod volume;
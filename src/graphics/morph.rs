@@ -0,0 +1,137 @@
+use super::instance::Instance;
+use instant::Instant;
+
+/* NOTE:
+    Switching states morphs the old cloud into the new one rather than
+    swapping it. Every new particle is paired with an old one and moves from
+    it to its own place, size and colour.
+
+    The pairing goes by quantile of distance from the nucleus: the k-th
+    closest of m new particles starts from the old particle the same fraction
+    of the way out, so inner shells grow out of inner shells and the cloud
+    as a whole swells or shrinks instead of scattering. A true optimal
+    assignment would move less but costs far too much at these counts.
+
+    The new cloud comes in chunks, and each chunk is its own sample of the
+    whole density, so each is paired on its own and starts moving when it
+    arrives.
+*/
+
+fn radius(instance: &Instance) -> f32 {
+    instance.position.iter().map(|x| x * x).sum::<f32>().sqrt()
+}
+
+fn by_radius(instances: &[Instance]) -> Vec<usize> {
+    let radii: Vec<f32> = instances.iter().map(radius).collect();
+    let mut order: Vec<usize> = (0..instances.len()).collect();
+    order.sort_unstable_by(|&a, &b| radii[a].total_cmp(&radii[b]));
+    order
+}
+
+fn smoothstep(t: f32) -> f32 {
+    let t = t.clamp(0., 1.);
+    t * t * (3. - 2. * t)
+}
+
+pub struct Morph {
+    // the old cloud, nearest first
+    sources: Vec<Instance>,
+    // where each new particle starts from
+    starts: Vec<Instance>,
+    // the end of each chunk in `starts` and when it arrived
+    chunks: Vec<(usize, Instant)>,
+}
+
+impl Morph {
+    pub fn new(old: &[Instance]) -> Morph {
+        Morph {
+            sources: by_radius(old).into_iter().map(|i| old[i]).collect(),
+            starts: Vec::new(),
+            chunks: Vec::new(),
+        }
+    }
+
+    /// pairs the next chunk of the new cloud with the old one
+    pub fn add(&mut self, targets: &[Instance], at: Instant) {
+        let (n, m) = (self.sources.len(), targets.len());
+        let mut starts = targets.to_vec();
+        for (k, i) in by_radius(targets).into_iter().enumerate() {
+            let quantile = (k as f32 + 0.5) / m as f32;
+            let source = ((quantile * n as f32) as usize).min(n.saturating_sub(1));
+            // with nothing to come from, a particle grows out of its place
+            starts[i] = match self.sources.get(source) {
+                Some(source) => *source,
+                None => Instance {
+                    radius: 0.,
+                    ..targets[i]
+                },
+            };
+        }
+        self.starts.extend(starts);
+        self.chunks.push((self.starts.len(), at));
+    }
+
+    /// the cloud part way between old and new, for the `targets` the chunks
+    /// were added for
+    pub fn frame(&self, targets: &[Instance], now: Instant, seconds: f32) -> Vec<Instance> {
+        let mut instances = Vec::with_capacity(targets.len());
+        let mut begin = 0;
+        for &(end, at) in &self.chunks {
+            let t = smoothstep((now - at).as_secs_f32() / seconds);
+            let lerp = |a: f32, b: f32| a + (b - a) * t;
+            let lerp3 = |a: [f32; 3], b: [f32; 3]| [0, 1, 2].map(|c| lerp(a[c], b[c]));
+            for (start, target) in self.starts[begin..end].iter().zip(&targets[begin..end]) {
+                instances.push(Instance {
+                    position: lerp3(start.position, target.position),
+                    radius: lerp(start.radius, target.radius),
+                    color: lerp3(start.color, target.color),
+                });
+            }
+            begin = end;
+        }
+        // anything not added yet is drawn where it is
+        instances.extend_from_slice(&targets[instances.len().min(targets.len())..]);
+        instances
+    }
+
+    /// whether every chunk added has finished moving
+    pub fn done(&self, now: Instant, seconds: f32) -> bool {
+        self.chunks
+            .last()
+            .is_none_or(|&(_, at)| (now - at).as_secs_f32() >= seconds)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Morph;
+    use crate::graphics::instance::Instance;
+    use instant::{Duration, Instant};
+
+    #[test]
+    fn test_morph() {
+        let at = |r: f32| Instance {
+            position: [r, 0., 0.],
+            radius: 1.,
+            color: [r; 3],
+        };
+        let old = [at(3.), at(1.), at(2.)];
+        let new = [at(20.), at(40.), at(30.), at(10.)];
+        let start = Instant::now();
+        let mut morph = Morph::new(&old);
+        morph.add(&new, start);
+
+        // the nearest new particle starts from the nearest old one
+        let frame = morph.frame(&new, start, 1.);
+        let xs: Vec<f32> = frame.iter().map(|i| i.position[0]).collect();
+        assert_eq!(xs, [2., 3., 2., 1.]);
+        assert!(!morph.done(start, 1.));
+
+        // half way in time is half way in space, by symmetry of the easing
+        let half = morph.frame(&new, start + Duration::from_millis(500), 1.);
+        assert!((half[3].position[0] - 5.5).abs() < 1e-4);
+        let end = start + Duration::from_secs(1);
+        assert!(morph.done(end, 1.));
+        assert_eq!(morph.frame(&new, end, 1.)[1].position, [40., 0., 0.]);
+    }
+}
@@ -0,0 +1,178 @@
+use super::camera::{Camera, check_view};
+use super::rows::parse_rows;
+use anyhow::bail;
+use cgmath::{Deg, Point3, Vector3};
+
+// where a path is read from, next to where the charts are written
+pub const PATH_FILE: &str = "camera_path.txt";
+
+/* NOTE:
+    A camera path is a list of keyframes, one a line:
+
+        # time  eye x y z      target x y z   fov
+        0       30 0 10        0 0 0          45
+        4       0 30 10        0 0 0          45
+        8       -30 0 10       0 0 0          30
+
+    with time in seconds, positions in a0 and the vertical field of view in
+    degrees. Blank lines and lines starting with '#' are skipped. Eye, target
+    and fov each follow a Catmull-Rom spline through the keyframes, so the
+    camera passes through every one without stopping, and where the camera
+    is depends only on the time, not on how the frames fell. The view is
+    kept with z up, as the turntable does, so no keyframe can look along z.
+    Playing starts from the first keyframe's time, whatever that is.
+*/
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Keyframe {
+    pub time: f32,
+    pub eye: Point3<f32>,
+    pub target: Point3<f32>,
+    pub fovy: Deg<f32>,
+}
+
+impl Keyframe {
+    fn values(&self) -> [f32; 7] {
+        let (e, t) = (self.eye, self.target);
+        [e.x, e.y, e.z, t.x, t.y, t.z, self.fovy.0]
+    }
+
+    fn from_values(time: f32, v: [f32; 7]) -> Keyframe {
+        Keyframe {
+            time,
+            eye: Point3::new(v[0], v[1], v[2]),
+            target: Point3::new(v[3], v[4], v[5]),
+            fovy: Deg(v[6]),
+        }
+    }
+
+    /// puts the camera here, looking with z up
+    pub fn apply(&self, camera: &mut Camera) {
+        camera.eye = self.eye;
+        camera.target = self.target;
+        camera.up = Vector3::unit_z();
+        camera.fovy = self.fovy.into();
+    }
+}
+
+pub struct CameraPath {
+    keys: Vec<Keyframe>,
+}
+
+impl CameraPath {
+    pub fn parse(text: &str) -> anyhow::Result<CameraPath> {
+        let mut keys: Vec<Keyframe> = Vec::new();
+        for (line, [time, values @ ..]) in parse_rows::<8>(text, "time, eye, target and fov")? {
+            let key = Keyframe::from_values(time, values);
+            if !time.is_finite() {
+                bail!("line {line}: every number has to be finite");
+            }
+            if let Err(e) = check_view(key.eye, key.target, Vector3::unit_z(), key.fovy) {
+                bail!("line {line}: {e}");
+            }
+            if keys.last().is_some_and(|key| time <= key.time) {
+                bail!("line {line}: keyframe times have to go up");
+            }
+            keys.push(key);
+        }
+        if keys.is_empty() {
+            bail!("no keyframes in camera path");
+        }
+        Ok(CameraPath { keys })
+    }
+
+    /// seconds from the first keyframe to the last
+    pub fn duration(&self) -> f32 {
+        self.keys.last().unwrap().time - self.keys[0].time
+    }
+
+    /// the camera `time` seconds after the first keyframe, held at the first
+    /// and last keyframes outside the path
+    pub fn at(&self, time: f32) -> Keyframe {
+        let keys = &self.keys;
+        let time = keys[0].time + time;
+        let i = keys.partition_point(|key| key.time <= time);
+        if i == 0 {
+            return Keyframe { time, ..keys[0] };
+        }
+        if i == keys.len() {
+            return Keyframe {
+                time,
+                ..keys[i - 1]
+            };
+        }
+
+        // cubic Hermite between keys i - 1 and i, with Catmull-Rom slopes
+        // that allow for uneven spacing in time
+        let slope = |k: usize| {
+            let (a, b) = (k.saturating_sub(1), (k + 1).min(keys.len() - 1));
+            let (va, vb) = (keys[a].values(), keys[b].values());
+            let dt = keys[b].time - keys[a].time;
+            std::array::from_fn::<f32, 7, _>(|c| (vb[c] - va[c]) / dt)
+        };
+        let (k0, k1) = (&keys[i - 1], &keys[i]);
+        let (v0, v1) = (k0.values(), k1.values());
+        let (m0, m1) = (slope(i - 1), slope(i));
+        let h = k1.time - k0.time;
+        let s = (time - k0.time) / h;
+        let (s2, s3) = (s * s, s * s * s);
+        let values = std::array::from_fn(|c| {
+            (2. * s3 - 3. * s2 + 1.) * v0[c]
+                + (s3 - 2. * s2 + s) * h * m0[c]
+                + (-2. * s3 + 3. * s2) * v1[c]
+                + (s3 - s2) * h * m1[c]
+        });
+        Keyframe::from_values(time, values)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CameraPath;
+    use cgmath::{Deg, Point3};
+
+    #[test]
+    fn test_path() {
+        let path = CameraPath::parse(
+            "# a quarter turn, then a zoom\n\
+             0  30 0 0   0 0 0  45\n\
+             \n\
+             2  0 30 0   0 0 0  45\n\
+             3  0 20 0   0 0 0  30\n",
+        )
+        .unwrap();
+        assert_eq!(path.duration(), 3.);
+
+        // through every keyframe, and held past the ends
+        assert_eq!(path.at(2.).eye, Point3::new(0., 30., 0.));
+        assert_eq!(path.at(3.).fovy, Deg(30.));
+        assert_eq!(path.at(-1.).eye, Point3::new(30., 0., 0.));
+        assert_eq!(path.at(10.).eye, Point3::new(0., 20., 0.));
+        // smooth in between, the same every time
+        let middle = path.at(1.);
+        assert!(middle.eye.x > 10. && middle.eye.y > 10.);
+        assert_eq!(middle, path.at(1.));
+
+        // a path can start at any time, and plays from there
+        let late = CameraPath::parse("-2  30 0 0  0 0 0  45\n1  0 30 0  0 0 0  45").unwrap();
+        assert_eq!(late.duration(), 3.);
+        assert_eq!(late.at(0.).eye, Point3::new(30., 0., 0.));
+        assert_eq!(late.at(3.).eye, Point3::new(0., 30., 0.));
+
+        assert!(CameraPath::parse("0 1 2 3").is_err());
+        assert!(CameraPath::parse("1 0 0 1 0 0 0 45\n0 0 0 1 0 0 0 45").is_err());
+        assert!(CameraPath::parse("# nothing").is_err());
+        // no view to be had from these
+        for bad in [
+            "0  0 0 0  0 0 0  45",
+            "0  0 0 10  0 0 0  45",
+            "0  30 0 0  0 0 0  180",
+            "0  30 0 0  0 0 0  0",
+            "0  30 0 NaN  0 0 0  45",
+            "inf  30 0 0  0 0 0  45",
+        ] {
+            let error = CameraPath::parse(bad).err().unwrap();
+            assert!(error.to_string().starts_with("line 1: "), "{error}");
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use super::camera::Camera;
+use super::vertex::Vertex;
+use crate::charts::{format_tick, nice_step};
+use std::ops::Range;
+
+const AXIS_COLORS: [[f32; 3]; 3] = [[0.9, 0.15, 0.1], [0.1, 0.7, 0.15], [0.1, 0.3, 0.95]];
+// the negative half of each axis is drawn fainter
+const NEGATIVE_AXIS_SHADE: f32 = 0.4;
+const GRID_COLOR: [f32; 3] = [0.45, 0.45, 0.45];
+const NUCLEUS_COLOR: [f32; 3] = [1.0, 0.75, 0.0];
+const GRID_LINES: usize = 10;
+const NUCLEUS_SCALE: f32 = 0.015;
+// the scale bar is picked to be about this much of the screen width
+const SCALE_BAR_FRACTION: f64 = 0.12;
+// right end of the scale bar, in clip space
+pub const SCALE_BAR_END: [f32; 2] = [0.9, -0.9];
+const SCALE_BAR_TICK: f32 = 0.02;
+
+pub const ANGSTROM_PER_BOHR: f64 = 0.529_177_210_9;
+
+// the unit the scale bar is labelled in, world units are always a0
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum LengthUnit {
+    Bohr,
+    Angstrom,
+}
+
+impl LengthUnit {
+    pub fn next(self) -> LengthUnit {
+        match self {
+            LengthUnit::Bohr => LengthUnit::Angstrom,
+            LengthUnit::Angstrom => LengthUnit::Bohr,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            LengthUnit::Bohr => "a0",
+            LengthUnit::Angstrom => "Å",
+        }
+    }
+
+    fn per_bohr(self) -> f64 {
+        match self {
+            LengthUnit::Bohr => 1.,
+            LengthUnit::Angstrom => ANGSTROM_PER_BOHR,
+        }
+    }
+}
+
+fn line(vertices: &mut Vec<Vertex>, a: [f32; 3], b: [f32; 3], color: [f32; 3]) {
+    vertices.push(Vertex { position: a, color });
+    vertices.push(Vertex { position: b, color });
+}
+
+/// x, y and z through the origin, `length` out each way, as a LineList
+pub fn axes_vertices(length: f32) -> Vec<Vertex> {
+    let mut vertices = Vec::with_capacity(12);
+    for (axis, color) in AXIS_COLORS.into_iter().enumerate() {
+        let mut end = [0.; 3];
+        end[axis] = length;
+        line(&mut vertices, [0.; 3], end, color);
+        end[axis] = -length;
+        line(
+            &mut vertices,
+            [0.; 3],
+            end,
+            color.map(|c| c * NEGATIVE_AXIS_SHADE),
+        );
+    }
+    vertices
+}
+
+/// square grid on the xz plane, the ground for the y up camera, with round
+/// number spacing that covers `extent`
+pub fn grid_vertices(extent: f32) -> Vec<Vertex> {
+    let step = nice_step(extent as f64, GRID_LINES / 2) as f32;
+    let lines = (extent / step).ceil() as i32;
+    let edge = lines as f32 * step;
+    let mut vertices = Vec::new();
+    for i in -lines..=lines {
+        let at = i as f32 * step;
+        line(&mut vertices, [at, 0., -edge], [at, 0., edge], GRID_COLOR);
+        line(&mut vertices, [-edge, 0., at], [edge, 0., at], GRID_COLOR);
+    }
+    vertices
+}
+
+/// a small wire octahedron at the origin
+pub fn nucleus_vertices(size: f32) -> Vec<Vertex> {
+    let tips = [
+        [size, 0., 0.],
+        [-size, 0., 0.],
+        [0., size, 0.],
+        [0., -size, 0.],
+        [0., 0., size],
+        [0., 0., -size],
+    ];
+    let mut vertices = Vec::with_capacity(24);
+    // every tip joins the four that aren't opposite it
+    for a in 0..6 {
+        for b in (a + 1)..6 {
+            if a / 2 != b / 2 {
+                line(&mut vertices, tips[a], tips[b], NUCLEUS_COLOR);
+            }
+        }
+    }
+    vertices
+}
+
+/// the world space reference lines in one LineList, with the range of
+/// vertices each of them takes so they can be toggled apart
+pub struct ReferenceLines {
+    pub vertices: Vec<Vertex>,
+    pub axes: Range<u32>,
+    pub grid: Range<u32>,
+    pub nucleus: Range<u32>,
+}
+
+impl ReferenceLines {
+    pub fn new(extent: f32) -> ReferenceLines {
+        let mut vertices = axes_vertices(extent);
+        let axes = 0..vertices.len() as u32;
+        vertices.extend(grid_vertices(extent));
+        let grid = axes.end..vertices.len() as u32;
+        vertices.extend(nucleus_vertices(extent * NUCLEUS_SCALE));
+        let nucleus = grid.end..vertices.len() as u32;
+        ReferenceLines {
+            vertices,
+            axes,
+            grid,
+            nucleus,
+        }
+    }
+}
+
+/// a horizontal bar in clip space with end ticks, a round length in `unit`
+/// as seen at the camera's target, and its label
+pub fn scale_bar(camera: &Camera, unit: LengthUnit, color: [f32; 3]) -> (Vec<Vertex>, String) {
+    // the view's height at the target, which is all of it when orthographic
+    let height = camera.view_height() as f64;
+    let width = height * camera.aspect as f64;
+
+    let length = nice_step(width * unit.per_bohr() * SCALE_BAR_FRACTION, 1);
+    let clip_length = (2. * length / unit.per_bohr() / width) as f32;
+
+    let [x1, y] = SCALE_BAR_END;
+    let x0 = x1 - clip_length;
+    let mut vertices = Vec::with_capacity(6);
+    line(&mut vertices, [x0, y, 0.], [x1, y, 0.], color);
+    for x in [x0, x1] {
+        line(
+            &mut vertices,
+            [x, y - SCALE_BAR_TICK, 0.],
+            [x, y + SCALE_BAR_TICK, 0.],
+            color,
+        );
+    }
+    (
+        vertices,
+        format!("{} {}", format_tick(length), unit.symbol()),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::{LengthUnit, scale_bar};
+    use crate::graphics::camera::Camera;
+
+    #[test]
+    fn test_scale_bar() {
+        let mut camera = Camera::new();
+        camera.aspect = 1.5;
+        let bar_width = |camera: &Camera, unit| {
+            let (vertices, label) = scale_bar(camera, unit, [0.; 3]);
+            (vertices[1].position[0] - vertices[0].position[0], label)
+        };
+
+        // between the fraction and 2.5 times it, of the 2 wide clip space
+        let (width, label) = bar_width(&camera, LengthUnit::Bohr);
+        assert!(width > 0.24 && width < 0.6, "{width}");
+        assert_eq!(label, "5 a0");
+
+        // zooming in shrinks what the bar stands for, not its size
+        camera.eye.z /= 4.;
+        let (width, label) = bar_width(&camera, LengthUnit::Bohr);
+        assert!(width > 0.24 && width < 0.6, "{width}");
+        assert_eq!(label, "2 a0");
+
+        let (_, label) = bar_width(&camera, LengthUnit::Angstrom);
+        assert_eq!(label, "1 Å");
+    }
+}
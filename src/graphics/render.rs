@@ -1,14 +1,76 @@
-use super::camera::{Camera, CameraController, CameraUniform};
+use super::bookmark::{BOOKMARK_FILE, Bookmarks, Pose};
+use super::camera::{Camera, CameraController, CameraMode, CameraUniform};
+use super::cloud::{self, RenderMode, Sampler, SpinView};
+use super::colormap::{self, ColorMap};
+use super::depth;
+use super::generate::{Generated, Generator, MAX_N, MAX_Z, Request, default_spin};
+use super::gui::{Gui, Style};
+use super::hud::{self, FrameTimer};
 use super::icosahedron::*;
-use super::vertex::Vertex;
+use super::instance::Instance;
+use super::morph::Morph;
+use super::path::{CameraPath, PATH_FILE};
+use super::reference::{self, LengthUnit, ReferenceLines};
+use super::slice::{self, ClipPlane, MAX_CLIP_PLANES, SLICE_RESO};
+use super::text::{self, TextBatch, TextVertex};
+use super::touch::{Gesture, Touches};
+use super::vertex::{SurfaceVertex, Vertex};
+use super::volume::{self, Transfer, VolumeUniform};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::charts;
+use crate::particle_gen::{Orbital, Particle, wavefunction::Wavefunction};
+use cgmath::Vector3;
+use instant::Instant;
 use std::sync::Arc;
 use wgpu;
 use wgpu::util::DeviceExt;
-use winit::{event_loop::ActiveEventLoop, keyboard::KeyCode, window::Window};
+use winit::{
+    event::{MouseScrollDelta, Touch, TouchPhase},
+    event_loop::ActiveEventLoop,
+    keyboard::KeyCode,
+    window::Window,
+};
 
 #[allow(dead_code)]
 const DEBUG: bool = false;
 
+const DEFAULT_STATE: (i32, i32, i32) = (2, 1, 1);
+const CLOUD_COUNT: usize = 20000;
+// what the panel lets the cloud be set to
+const PARTICLE_RANGE: (usize, usize) = (1000, 2_000_000);
+const POINT_SIZE_RANGE: (f32, f32) = (0.25, 4.);
+// how long a change of state takes to morph the cloud, none when 0
+const MORPH_SECONDS: f32 = 1.;
+const MORPH_RANGE: (f32, f32) = (0., 3.);
+// how long the camera takes to speed up and slow down, none when 0
+const INERTIA_RANGE: (f32, f32) = (0., 1.);
+const PARTICLE_SCALE: f64 = 0.003;
+const SCALE_BAR_VERTICES: usize = 6;
+const SCALE_BAR_COLOR: [f32; 3] = [0.05, 0.05, 0.05];
+const SCALE_BAR_DARK_COLOR: [f32; 3] = [0.9, 0.9, 0.9];
+// font pixels per window pixel at a scale factor of 1
+const TEXT_SCALE: f64 = 2.;
+// touchpads scroll by pixels, counted as wheel lines of this many
+const PIXELS_PER_LINE: f64 = 40.;
+// gap between the HUD and the window edge, and around its text, in pixels
+const HUD_MARGIN: f32 = 8.;
+const HUD_TEXT_COLOR: [f32; 4] = [0.05, 0.05, 0.05, 1.];
+const HUD_PANEL_COLOR: [f32; 4] = [1., 1., 1., 0.75];
+const HUD_DARK_TEXT_COLOR: [f32; 4] = [0.9, 0.9, 0.9, 1.];
+const HUD_DARK_PANEL_COLOR: [f32; 4] = [0., 0., 0., 0.6];
+const HUD_FIELD_COLOR: [f32; 4] = [0.82, 0.83, 0.87, 1.];
+const HUD_DARK_FIELD_COLOR: [f32; 4] = [0.2, 0.2, 0.25, 1.];
+const HUD_ACCENT_COLOR: [f32; 4] = [0.45, 0.6, 0.95, 1.];
+// a clip plane crosses the orbital in this many key presses
+const CLIP_STEPS: f64 = 40.;
+// additive sprites and the volume need a dark background to glow against
+const DARK_BACKGROUND: wgpu::Color = wgpu::Color {
+    r: 0.01,
+    g: 0.01,
+    b: 0.02,
+    a: 1.,
+};
+
 pub struct State {
     pub window: Arc<Window>,
     device: wgpu::Device,
@@ -18,16 +80,82 @@ pub struct State {
     config: wgpu::SurfaceConfiguration,
     surface: wgpu::Surface<'static>,
     is_surface_configured: bool,
+    depth_view: wgpu::TextureView,
     surface_format: wgpu::TextureFormat,
     render_pipeline: wgpu::RenderPipeline,
     vertex_buffer: wgpu::Buffer,
     index_buffer: wgpu::Buffer,
     num_indices: u32,
+    line_pipeline: wgpu::RenderPipeline,
+    flow_buffer: wgpu::Buffer,
+    num_flow_vertices: u32,
+    show_flow: bool,
+    surface_pipeline: wgpu::RenderPipeline,
+    surface_vertex_buffer: wgpu::Buffer,
+    surface_index_buffer: wgpu::Buffer,
+    num_surface_indices: u32,
+    show_surface: bool,
+    cloud: Vec<Particle>,
+    instance_buffer: wgpu::Buffer,
+    instance_capacity: usize,
+    num_instances: u32,
+    spin_view: SpinView,
+    billboard_pipeline: wgpu::RenderPipeline,
+    render_mode: RenderMode,
+    color_map: ColorMap,
+    reference: ReferenceLines,
+    reference_buffer: wgpu::Buffer,
+    show_axes: bool,
+    show_grid: bool,
+    show_nucleus: bool,
+    overlay_line_pipeline: wgpu::RenderPipeline,
+    scale_bar_buffer: wgpu::Buffer,
+    scale_label: String,
+    length_unit: LengthUnit,
+    show_scale_bar: bool,
+    volume_pipeline: wgpu::RenderPipeline,
+    volume_uniform: VolumeUniform,
+    volume_buffer: wgpu::Buffer,
+    volume_bind_group: wgpu::BindGroup,
+    transfer: Transfer,
+    clip_planes: Vec<ClipPlane>,
+    slice_pipeline: wgpu::RenderPipeline,
+    slice_texture: wgpu::Texture,
+    slice_bind_group: wgpu::BindGroup,
+    show_slice: bool,
+    overlay_pipeline: wgpu::RenderPipeline,
+    legend_buffer: wgpu::Buffer,
+    num_legend_vertices: u32,
+    text_pipeline: wgpu::RenderPipeline,
+    text_bind_group: wgpu::BindGroup,
+    text_buffer: wgpu::Buffer,
+    num_text_vertices: u32,
+    frame_timer: FrameTimer,
+    show_help: bool,
+    gui: Gui,
+    show_panel: bool,
+    point_size: f32,
+    morph_seconds: f32,
+    // the cloud's instances as they end up, and how they get there
+    instances: Vec<Instance>,
+    morph: Option<Morph>,
+    generator: Generator,
+    // the newest request, which what is drawn catches up to
+    requested: Request,
+    wavefunction: Wavefunction,
     camera: Camera,
     camera_uniform: CameraUniform,
     camera_buffer: wgpu::Buffer,
     camera_bind_group: wgpu::BindGroup,
     camera_controller: CameraController,
+    // a camera path being played, and how far into it
+    playing: Option<(CameraPath, f32)>,
+    bookmarks: Bookmarks,
+    // held down, digits save a bookmark rather than recall it
+    shift: bool,
+    touches: Touches,
+    // a finger working the control panel rather than the camera
+    panel_finger: Option<u64>,
 }
 
 impl State {
@@ -106,6 +234,8 @@ impl State {
             view_formats: vec![],
             desired_maximum_frame_latency: 2,
         };
+        let depth_view = depth::create_depth_view(&device, size);
+
         // camera
         let camera = Camera::new();
 
@@ -139,7 +269,7 @@ impl State {
             }],
             label: Some("camera_bind_group"),
         });
-        let camera_controller = CameraController::new(0.2, 0.01);
+        let camera_controller = CameraController::new(12., 0.6);
         /*
         Shader pipeline here
         */
@@ -159,7 +289,7 @@ impl State {
             vertex: wgpu::VertexState {
                 module: &shader,
                 entry_point: Some("vs_main"),
-                buffers: &[Vertex::desc()],
+                buffers: &[Vertex::desc(), Instance::desc()],
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
             },
             fragment: Some(wgpu::FragmentState {
@@ -186,7 +316,7 @@ impl State {
                 // Requires Features::CONSERVATIVE_RASTERIZATION
                 conservative: false,
             },
-            depth_stencil: None, // 1.
+            depth_stencil: Some(depth::depth_state(true)), // 1.
             multisample: wgpu::MultisampleState {
                 count: 1,                         // 2.
                 mask: !0,                         // 3.
@@ -207,6 +337,355 @@ impl State {
         });
         let num_indices = INDICES.len() as u32;
 
+        /*
+        Probability current arrows
+        */
+        let line_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Line Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/line.wgsl").into()),
+        });
+        let line_pipeline = flat_pipeline(
+            &device,
+            &render_pipeline_layout,
+            &line_shader,
+            config.format,
+            wgpu::PrimitiveTopology::LineList,
+        );
+
+        let billboard_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Billboard Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/billboard.wgsl").into()),
+        });
+        let billboard_pipeline = billboard_pipeline(
+            &device,
+            &render_pipeline_layout,
+            &billboard_shader,
+            config.format,
+        );
+
+        // the first state is waited for, there is nothing to draw before it
+        let (n, l, m) = DEFAULT_STATE;
+        let requested = Request {
+            n,
+            l,
+            m,
+            z: 1,
+            spin_view: SpinView::Off,
+            count: CLOUD_COUNT,
+            sampler: Sampler::InverseCdf,
+        };
+        let mut generator = Generator::new();
+        generator.request(requested);
+        let Generated {
+            cloud,
+            scene: Some(scene),
+            ..
+        } = generator.wait()
+        else {
+            unreachable!("the first request always builds the scene")
+        };
+        let wavefunction = requested.wavefunction();
+        let flow_vertices = scene.flow;
+        let flow_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Flow Buffer"),
+            contents: bytemuck::cast_slice(&flow_vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let num_flow_vertices = flow_vertices.len() as u32;
+
+        /*
+        Isosurface enclosing most of the probability
+        */
+        let surface_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Surface Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/surface.wgsl").into()),
+        });
+        let surface_pipeline = surface_pipeline(
+            &device,
+            &render_pipeline_layout,
+            &surface_shader,
+            config.format,
+        );
+        let iso = scene.surface;
+        let surface_vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Surface Vertex Buffer"),
+            contents: bytemuck::cast_slice(&iso.vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let surface_index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Surface Index Buffer"),
+            contents: bytemuck::cast_slice(&iso.indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        let num_surface_indices = iso.indices.len() as u32;
+
+        let spin_view = requested.spin_view;
+        let color_map = ColorMap::Sign;
+        let point_size = 1.;
+        let instances = cloud::instances(
+            &cloud,
+            spin_view,
+            color_map,
+            particle_radius(&wavefunction, point_size),
+        );
+        // room for the whole cloud, the rest of it comes in chunks
+        let instance_capacity = requested.count.max(instances.len());
+        let instance_buffer = instance_buffer(&device, instance_capacity);
+        queue.write_buffer(&instance_buffer, 0, bytemuck::cast_slice(&instances));
+        let num_instances = instances.len() as u32;
+
+        /*
+        Raymarched volume, evaluates ψ per pixel from uniform coefficients
+        */
+        let transfer = Transfer::default();
+        let volume_uniform = VolumeUniform::new(&wavefunction, &camera, transfer, color_map);
+        let volume_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Volume Buffer"),
+            contents: bytemuck::cast_slice(&[volume_uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let volume_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+                label: Some("volume_bind_group_layout"),
+            });
+        let volume_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &volume_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: volume_buffer.as_entire_binding(),
+            }],
+            label: Some("volume_bind_group"),
+        });
+        let volume_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Volume Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/volume.wgsl").into()),
+        });
+        let volume_pipeline = volume_pipeline(
+            &device,
+            &[&camera_bind_group_layout, &volume_bind_group_layout],
+            &volume_shader,
+            config.format,
+        );
+
+        /*
+        Heatmap of the density on a plane, shown in a corner
+        */
+        let slice_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Slice Texture"),
+            size: wgpu::Extent3d {
+                width: SLICE_RESO,
+                height: SLICE_RESO,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let slice_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let slice_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+                label: Some("slice_bind_group_layout"),
+            });
+        let slice_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &slice_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(
+                        &slice_texture.create_view(&wgpu::TextureViewDescriptor::default()),
+                    ),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&slice_sampler),
+                },
+            ],
+            label: Some("slice_bind_group"),
+        });
+        let slice_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Slice Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/slice.wgsl").into()),
+        });
+        let slice_pipeline = slice_pipeline(
+            &device,
+            &[&camera_bind_group_layout, &slice_bind_group_layout],
+            &slice_shader,
+            config.format,
+        );
+
+        /*
+        Screen space overlays, the colour map legend
+        */
+        let overlay_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Overlay Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/overlay.wgsl").into()),
+        });
+        let overlay_line_pipeline = overlay_pipeline(
+            &device,
+            &overlay_shader,
+            config.format,
+            wgpu::PrimitiveTopology::LineList,
+        );
+        let overlay_pipeline = overlay_pipeline(
+            &device,
+            &overlay_shader,
+            config.format,
+            wgpu::PrimitiveTopology::TriangleList,
+        );
+        // rewritten every frame as the camera zooms
+        let scale_bar_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Scale Bar Buffer"),
+            size: (SCALE_BAR_VERTICES * std::mem::size_of::<Vertex>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        /*
+        Reference lines: axes, ground grid and the nucleus. They are world
+        space lines like the flow arrows, so they share line_pipeline and one
+        buffer, each toggled by drawing its own range of it. The scale bar is
+        in screen space and has overlay_line_pipeline instead.
+        */
+        let reference = ReferenceLines::new(wavefunction.extent() as f32);
+        let reference_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Reference Buffer"),
+            contents: bytemuck::cast_slice(&reference.vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let legend_vertices = color_map.legend_vertices();
+        let legend_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Legend Buffer"),
+            contents: bytemuck::cast_slice(&legend_vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let num_legend_vertices = legend_vertices.len() as u32;
+
+        /*
+        Text, drawn from an atlas of the bitmap font
+        */
+        let atlas_size = wgpu::Extent3d {
+            width: text::ATLAS_WIDTH,
+            height: text::ATLAS_HEIGHT,
+            depth_or_array_layers: 1,
+        };
+        let atlas_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Glyph Atlas"),
+            size: atlas_size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &atlas_texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &text::atlas(),
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(text::ATLAS_WIDTH),
+                rows_per_image: Some(text::ATLAS_HEIGHT),
+            },
+            atlas_size,
+        );
+        // the glyphs are drawn at whole multiples of their size, so nearest
+        // sampling keeps them sharp
+        let atlas_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+        let text_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+                label: Some("text_bind_group_layout"),
+            });
+        let text_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &text_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(
+                        &atlas_texture.create_view(&wgpu::TextureViewDescriptor::default()),
+                    ),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&atlas_sampler),
+                },
+            ],
+            label: Some("text_bind_group"),
+        });
+        let text_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Text Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/text.wgsl").into()),
+        });
+        let text_pipeline = text_pipeline(
+            &device,
+            &text_bind_group_layout,
+            &text_shader,
+            config.format,
+        );
+        // grown whenever a frame's text doesn't fit
+        let text_buffer = text_buffer(&device, 0);
+
         let state = State {
             window,
             device,
@@ -215,16 +694,77 @@ impl State {
             config,
             surface,
             is_surface_configured: false,
+            depth_view,
             surface_format,
             render_pipeline,
             vertex_buffer,
             index_buffer,
             num_indices,
+            line_pipeline,
+            flow_buffer,
+            num_flow_vertices,
+            show_flow: true,
+            surface_pipeline,
+            surface_vertex_buffer,
+            surface_index_buffer,
+            num_surface_indices,
+            show_surface: false,
+            cloud,
+            instance_buffer,
+            instance_capacity,
+            num_instances,
+            spin_view,
+            billboard_pipeline,
+            render_mode: RenderMode::Mesh,
+            color_map,
+            reference,
+            reference_buffer,
+            show_axes: true,
+            show_grid: false,
+            show_nucleus: true,
+            overlay_line_pipeline,
+            scale_bar_buffer,
+            scale_label: String::new(),
+            length_unit: LengthUnit::Bohr,
+            show_scale_bar: true,
+            volume_pipeline,
+            volume_uniform,
+            volume_buffer,
+            volume_bind_group,
+            transfer,
+            clip_planes: Vec::new(),
+            slice_pipeline,
+            slice_texture,
+            slice_bind_group,
+            show_slice: false,
+            overlay_pipeline,
+            legend_buffer,
+            num_legend_vertices,
+            text_pipeline,
+            text_bind_group,
+            text_buffer,
+            num_text_vertices: 0,
+            frame_timer: FrameTimer::new(),
+            show_help: false,
+            gui: Gui::default(),
+            show_panel: false,
+            point_size,
+            morph_seconds: MORPH_SECONDS,
+            instances,
+            morph: None,
+            generator,
+            requested,
+            wavefunction,
             camera,
             camera_uniform,
             camera_buffer,
             camera_bind_group,
             camera_controller,
+            playing: None,
+            bookmarks: load_bookmarks(),
+            shift: false,
+            touches: Touches::default(),
+            panel_finger: None,
         };
 
         // Configure surface for the first time
@@ -237,21 +777,680 @@ impl State {
     pub fn handle_key(&mut self, event_loop: &ActiveEventLoop, code: KeyCode, is_pressed: bool) {
         self.camera_controller.process_events(code, is_pressed);
         match (code, is_pressed) {
+            (KeyCode::ShiftLeft | KeyCode::ShiftRight, _) => self.shift = is_pressed,
+            (_, true) if bookmark_slot(code).is_some() => {
+                let slot = bookmark_slot(code).unwrap();
+                if self.shift {
+                    self.save_bookmark(slot);
+                } else {
+                    self.recall_bookmark(slot);
+                }
+            }
             (KeyCode::Escape, true) =>
             {
                 #[cfg(not(target_arch = "wasm32"))]
                 event_loop.exit()
             }
             (KeyCode::KeyR, true) => {
+                self.playing = None;
                 self.camera_controller.reset(&mut self.camera);
             }
+            (KeyCode::KeyO, true) => {
+                self.camera.orthographic = !self.camera.orthographic;
+            }
+            // looking along the axes, with ψ's z axis up in the side views as
+            // figures draw it
+            (KeyCode::F1, true) => self.snap_view(Vector3::unit_x(), Vector3::unit_z()),
+            (KeyCode::F2, true) => self.snap_view(Vector3::unit_y(), Vector3::unit_z()),
+            (KeyCode::F3, true) => self.snap_view(Vector3::unit_z(), Vector3::unit_y()),
+            (KeyCode::F4, true) => self.snap_view(Vector3::new(1., 1., 1.), Vector3::unit_z()),
+            (KeyCode::KeyJ, true) => {
+                self.camera_controller.toggle_turntable(&mut self.camera);
+            }
+            (KeyCode::KeyY, true) => self.toggle_path(),
+            (KeyCode::KeyZ, true) => {
+                self.camera_controller.toggle_mode();
+                log::info!("camera {:?}", self.camera_controller.mode());
+            }
+            (KeyCode::KeyF, true) => {
+                self.show_flow = !self.show_flow;
+            }
+            (KeyCode::KeyI, true) => {
+                self.show_surface = !self.show_surface;
+            }
+            (KeyCode::KeyX, true) => {
+                self.show_axes = !self.show_axes;
+            }
+            (KeyCode::KeyG, true) => {
+                self.show_grid = !self.show_grid;
+            }
+            (KeyCode::KeyN, true) => {
+                self.show_nucleus = !self.show_nucleus;
+            }
+            (KeyCode::KeyB, true) => {
+                self.show_scale_bar = !self.show_scale_bar;
+            }
+            (KeyCode::KeyU, true) => {
+                self.length_unit = self.length_unit.next();
+                self.update_scale_bar();
+                log::info!("scale bar {}", self.scale_label);
+            }
+            (KeyCode::KeyC, true) => {
+                #[cfg(not(target_arch = "wasm32"))]
+                match charts::export_charts(&self.wavefunction, std::path::Path::new(".")) {
+                    Ok(paths) => log::info!("wrote charts {paths:?}"),
+                    Err(e) => log::error!("couldn't write charts: {e}"),
+                }
+                #[cfg(target_arch = "wasm32")]
+                log::error!("couldn't write charts: there are no files on the web");
+            }
+            (KeyCode::KeyT, true) => {
+                let spin_view = self.requested.spin_view.next();
+                // only switching between spin colourings keeps the same particles
+                if (spin_view == SpinView::Off) != (self.requested.spin_view == SpinView::Off) {
+                    self.request(Request {
+                        spin_view,
+                        ..self.requested
+                    });
+                } else {
+                    self.requested.spin_view = spin_view;
+                    self.spin_view = spin_view;
+                    self.upload_cloud();
+                }
+            }
+            // step through the states, clamped to the valid ones
+            (KeyCode::ArrowUp, true) => self.request(self.requested.step(1, 0, 0)),
+            (KeyCode::ArrowDown, true) => self.request(self.requested.step(-1, 0, 0)),
+            (KeyCode::ArrowRight, true) => self.request(self.requested.step(0, 1, 0)),
+            (KeyCode::ArrowLeft, true) => self.request(self.requested.step(0, -1, 0)),
+            (KeyCode::PageUp, true) => self.request(self.requested.step(0, 0, 1)),
+            (KeyCode::PageDown, true) => self.request(self.requested.step(0, 0, -1)),
+            (KeyCode::KeyM, true) => {
+                self.render_mode = self.render_mode.next();
+            }
+            (KeyCode::KeyV, true) => {
+                self.set_color_map(self.color_map.next());
+            }
+            (KeyCode::KeyP, true) => {
+                self.set_color_map(self.color_map.next_gradient());
+            }
+            // clip planes: add one cutting away the half facing the camera,
+            // clear them, and slide the newest one along its normal
+            (KeyCode::KeyK, true) => {
+                if self.clip_planes.len() < MAX_CLIP_PLANES {
+                    self.clip_planes.push(ClipPlane::facing(self.camera.eye));
+                    self.update_slice();
+                }
+            }
+            (KeyCode::KeyL, true) => {
+                self.clip_planes.clear();
+                self.update_slice();
+            }
+            (KeyCode::Comma | KeyCode::Period, true) => {
+                let step = (self.wavefunction.extent() / CLIP_STEPS) as f32;
+                if let Some(plane) = self.clip_planes.last_mut() {
+                    plane.offset += if code == KeyCode::Period { step } else { -step };
+                    self.update_slice();
+                }
+            }
+            (KeyCode::Slash, true) => {
+                self.show_help = !self.show_help;
+            }
+            (KeyCode::Tab, true) => {
+                self.show_panel = !self.show_panel;
+            }
+            (KeyCode::KeyH, true) => {
+                self.show_slice = !self.show_slice;
+                self.update_slice();
+            }
+            // transfer function of the volume: opacity, then contrast
+            (KeyCode::BracketLeft, true) => {
+                self.transfer.gain = (self.transfer.gain / 1.25).max(0.05);
+                self.volume_uniform.set_transfer(self.transfer);
+            }
+            (KeyCode::BracketRight, true) => {
+                self.transfer.gain = (self.transfer.gain * 1.25).min(100.);
+                self.volume_uniform.set_transfer(self.transfer);
+            }
+            (KeyCode::Minus, true) => {
+                self.transfer.gamma = (self.transfer.gamma - 0.1).max(0.1);
+                self.volume_uniform.set_transfer(self.transfer);
+            }
+            (KeyCode::Equal, true) => {
+                self.transfer.gamma = (self.transfer.gamma + 0.1).min(2.);
+                self.volume_uniform.set_transfer(self.transfer);
+            }
             _ => {}
         }
     }
+
+    fn set_color_map(&mut self, color_map: ColorMap) {
+        self.color_map = color_map;
+        match color_map.legend_range() {
+            Some((low, high)) => log::info!("colour map {color_map:?}, {low} to {high}"),
+            None => log::info!("colour map {color_map:?}"),
+        }
+        let vertices = color_map.legend_vertices();
+        self.legend_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Legend Buffer"),
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+        self.num_legend_vertices = vertices.len() as u32;
+        self.volume_uniform =
+            VolumeUniform::new(&self.wavefunction, &self.camera, self.transfer, color_map);
+        self.update_slice();
+        self.upload_cloud();
+    }
+
+    fn update_scale_bar(&mut self) {
+        let color = match self.render_mode {
+            RenderMode::Mesh => SCALE_BAR_COLOR,
+            RenderMode::Billboard | RenderMode::Volume => SCALE_BAR_DARK_COLOR,
+        };
+        let (vertices, label) = reference::scale_bar(&self.camera, self.length_unit, color);
+        self.queue
+            .write_buffer(&self.scale_bar_buffer, 0, bytemuck::cast_slice(&vertices));
+        self.scale_label = label;
+    }
+
+    // the slice lies in the newest clip plane, or the xz plane without one
+    fn update_slice(&mut self) {
+        if !self.show_slice {
+            return;
+        }
+        let plane = self.clip_planes.last().copied().unwrap_or_default();
+        let pixels = slice::heatmap(
+            &self.wavefunction,
+            &plane,
+            self.wavefunction.extent(),
+            volume::max_density(&self.wavefunction),
+            self.color_map.gradient(),
+        );
+        self.queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.slice_texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            bytemuck::cast_slice(&pixels),
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * SLICE_RESO),
+                rows_per_image: Some(SLICE_RESO),
+            },
+            wgpu::Extent3d {
+                width: SLICE_RESO,
+                height: SLICE_RESO,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    // the text for this frame: the state readout in the top left, the key
+    // list under it, and the labels of the scale bar and legend
+    fn update_hud(&mut self) {
+        let scale = (self.window.scale_factor() * TEXT_SCALE).round() as u32;
+        let mut batch = TextBatch::new(self.size, scale);
+        let (text_color, panel_color) = match self.render_mode {
+            RenderMode::Mesh => (HUD_TEXT_COLOR, HUD_PANEL_COLOR),
+            RenderMode::Billboard | RenderMode::Volume => {
+                (HUD_DARK_TEXT_COLOR, HUD_DARK_PANEL_COLOR)
+            }
+        };
+        let panel = |batch: &mut TextBatch, text: &str, at: [f32; 2]| {
+            let [w, h] = batch.measure(text);
+            batch.rect(
+                [at[0] - HUD_MARGIN, at[1] - HUD_MARGIN],
+                [at[0] + w + HUD_MARGIN, at[1] + h + HUD_MARGIN],
+                panel_color,
+            );
+            batch.text(text, at, text_color);
+        };
+
+        let spin = (self.spin_view != SpinView::Off).then(|| default_spin(self.wavefunction.l()));
+        let mut status = hud::status(
+            &self.wavefunction,
+            spin,
+            self.num_instances as usize,
+            self.frame_timer.seconds(),
+        );
+        if self.generator.busy() {
+            let Request { n, l, m, .. } = self.requested;
+            status.push_str(&format!("\ngenerating ({n}, {l}, {m})"));
+        }
+        if self.camera_controller.mode() == CameraMode::Fly {
+            status.push_str("\nfree fly");
+        }
+        if self.camera.orthographic {
+            status.push_str("\northographic");
+        }
+        if self.camera_controller.turntable() {
+            status.push_str("\nturntable");
+        }
+        if let Some((path, time)) = &self.playing {
+            status.push_str(&format!("\npath {time:.1} / {:.1} s", path.duration()));
+        }
+        if !self.show_help {
+            status.push_str("\n/ for keys");
+        }
+        let at = [2. * HUD_MARGIN, 2. * HUD_MARGIN];
+        panel(&mut batch, &status, at);
+        let below = at[1] + batch.measure(&status)[1] + 3. * HUD_MARGIN;
+        // the key list goes beside the control panel when both are up
+        let mut help_at = [at[0], below];
+        if self.show_panel {
+            let style = Style {
+                text: text_color,
+                panel: panel_color,
+                field: match self.render_mode {
+                    RenderMode::Mesh => HUD_FIELD_COLOR,
+                    _ => HUD_DARK_FIELD_COLOR,
+                },
+                accent: HUD_ACCENT_COLOR,
+            };
+            let right = self.control_panel(&mut batch, [at[0], below], style);
+            help_at[0] = right + 3. * HUD_MARGIN;
+        }
+        if self.show_help {
+            panel(&mut batch, hud::KEY_HELP, help_at);
+        }
+
+        // the scale bar label sits on the bar, right aligned with it
+        if self.show_scale_bar {
+            let [x, y] = batch.to_pixels(reference::SCALE_BAR_END);
+            let [w, h] = batch.measure(&self.scale_label);
+            batch.text(&self.scale_label, [x - w, y - h - HUD_MARGIN], text_color);
+        }
+        // the legend's ends are labelled to its left
+        if let Some((low, high)) = self.color_map.legend_range() {
+            let (x0, _) = colormap::LEGEND_X;
+            let (y0, y1) = colormap::LEGEND_Y;
+            for (label, y) in [(low, y0), (high, y1)] {
+                let [x, y] = batch.to_pixels([x0, y]);
+                let [w, h] = batch.measure(label);
+                panel(&mut batch, label, [x - w - 2. * HUD_MARGIN, y - h / 2.]);
+            }
+        }
+
+        let bytes = bytemuck::cast_slice(&batch.vertices);
+        if bytes.len() as wgpu::BufferAddress > self.text_buffer.size() {
+            self.text_buffer = text_buffer(&self.device, bytes.len());
+        }
+        self.queue.write_buffer(&self.text_buffer, 0, bytes);
+        self.num_text_vertices = batch.vertices.len() as u32;
+    }
+
+    // the panel's widgets, with whatever they changed applied. Returns the
+    // panel's right edge.
+    fn control_panel(&mut self, batch: &mut TextBatch, at: [f32; 2], style: Style) -> f32 {
+        let mut request = self.requested;
+        let mut sampler = Sampler::ALL
+            .iter()
+            .position(|&s| s == request.sampler)
+            .unwrap_or(0);
+        let mut color_kind = self.color_map.kind();
+        let mut point_size = self.point_size;
+        let mut morph_seconds = self.morph_seconds;
+        let mut inertia = self.camera_controller.inertia();
+        let mut mode = RenderMode::ALL
+            .iter()
+            .position(|&r| r == self.render_mode)
+            .unwrap_or(0);
+
+        let mut ui = self.gui.begin(batch, at, style);
+        ui.int_slider("n", &mut request.n, 1..=MAX_N);
+        request.l = request.l.min(request.n - 1);
+        ui.int_slider("l", &mut request.l, 0..=request.n - 1);
+        request.m = request.m.clamp(-request.l, request.l);
+        ui.int_slider("m", &mut request.m, -request.l..=request.l);
+        ui.int_slider("Z", &mut request.z, 1..=MAX_Z);
+
+        // the count spans orders of magnitude, so the slider is logarithmic
+        // and the count kept to two significant figures
+        let (lo, hi) = PARTICLE_RANGE;
+        let span = (hi as f32 / lo as f32).ln();
+        let t = (request.count as f32 / lo as f32).ln() / span;
+        if let Some(t) = ui.slider("particles", &request.count.to_string(), t) {
+            let exact = lo as f32 * (t * span).exp();
+            let figure = 10f32.powi(exact.log10().floor() as i32 - 1);
+            request.count = ((exact / figure).round() * figure) as usize;
+        }
+        ui.dropdown("sampler", &mut sampler, &Sampler::ALL.map(Sampler::name));
+        request.sampler = Sampler::ALL[sampler];
+        let color_changed = ui.dropdown("colour", &mut color_kind, &ColorMap::KINDS);
+        let size_changed = ui.float_slider(
+            "size",
+            &mut point_size,
+            POINT_SIZE_RANGE.0..=POINT_SIZE_RANGE.1,
+            2,
+        );
+        ui.float_slider(
+            "morph s",
+            &mut morph_seconds,
+            MORPH_RANGE.0..=MORPH_RANGE.1,
+            1,
+        );
+        ui.float_slider(
+            "inertia s",
+            &mut inertia,
+            INERTIA_RANGE.0..=INERTIA_RANGE.1,
+            2,
+        );
+        if ui.dropdown("render", &mut mode, &RenderMode::ALL.map(RenderMode::name)) {
+            self.render_mode = RenderMode::ALL[mode];
+        }
+        let (_, [right, _]) = ui.end();
+
+        self.request(request);
+        self.point_size = point_size;
+        self.morph_seconds = morph_seconds;
+        self.camera_controller.set_inertia(inertia);
+        if color_changed {
+            self.set_color_map(ColorMap::from_kind(color_kind, self.color_map.gradient()));
+        } else if size_changed {
+            self.upload_cloud();
+        }
+        right
+    }
+
+    // saves where the camera is to the slot, and every slot to BOOKMARK_FILE,
+    // which on the web is the browser's local storage
+    fn save_bookmark(&mut self, slot: usize) {
+        self.bookmarks.set(slot, Pose::of(&self.camera));
+        log::info!("bookmarked view {slot}");
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Err(e) = std::fs::write(BOOKMARK_FILE, self.bookmarks.to_text()) {
+            log::error!("couldn't write {BOOKMARK_FILE}: {e}");
+        }
+        #[cfg(target_arch = "wasm32")]
+        if !local_storage().is_some_and(|storage| {
+            storage
+                .set_item(BOOKMARK_FILE, &self.bookmarks.to_text())
+                .is_ok()
+        }) {
+            log::error!("couldn't keep {BOOKMARK_FILE} in the browser's storage");
+        }
+    }
+
+    fn recall_bookmark(&mut self, slot: usize) {
+        match self.bookmarks.get(slot) {
+            Some(pose) => {
+                self.playing = None;
+                pose.apply(&mut self.camera);
+                self.camera_controller.stop();
+            }
+            None => log::info!("no view bookmarked on {slot}, shift + {slot} saves one"),
+        }
+    }
+
+    // plays the path in PATH_FILE from the start, read again each time so
+    // it can be edited between runs, or stops it where it is
+    fn toggle_path(&mut self) {
+        if self.playing.take().is_some() {
+            self.camera_controller.stop();
+            return;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        match std::fs::read_to_string(PATH_FILE)
+            .map_err(anyhow::Error::from)
+            .and_then(|text| CameraPath::parse(&text))
+        {
+            Ok(path) => {
+                log::info!("playing {PATH_FILE}, {} s", path.duration());
+                self.playing = Some((path, 0.));
+            }
+            Err(e) => log::error!("couldn't play {PATH_FILE}: {e:#}"),
+        }
+        #[cfg(target_arch = "wasm32")]
+        log::error!("couldn't play {PATH_FILE}: there are no files on the web");
+    }
+
+    fn snap_view(&mut self, direction: Vector3<f32>, up: Vector3<f32>) {
+        self.camera.look_from(direction, up);
+        self.camera_controller.stop();
+    }
+
+    // asks for a new state or cloud, unless it is the one last asked for
+    fn request(&mut self, request: Request) {
+        if request != self.requested {
+            self.requested = request;
+            self.generator.request(request);
+        }
+    }
+
+    // swaps in whatever the generator finished, the state's geometry only
+    // comes with it when the state changed
+    fn apply(&mut self, generated: Generated) {
+        let request = generated.request;
+        let now = Instant::now();
+        let new_state = generated.scene.is_some();
+        if let Some(scene) = generated.scene {
+            // what is on screen, part way through a morph or not, morphs on
+            // into the new state
+            self.morph = (self.morph_seconds > 0.).then(|| match &self.morph {
+                Some(morph) => Morph::new(&morph.frame(&self.instances, now, self.morph_seconds)),
+                None => Morph::new(&self.instances),
+            });
+            self.wavefunction = request.wavefunction();
+            log::info!(
+                "state ({}, {}, {}), Z = {}",
+                request.n,
+                request.l,
+                request.m,
+                request.z
+            );
+
+            self.flow_buffer = self
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Flow Buffer"),
+                    contents: bytemuck::cast_slice(&scene.flow),
+                    usage: wgpu::BufferUsages::VERTEX,
+                });
+            self.num_flow_vertices = scene.flow.len() as u32;
+
+            let iso = scene.surface;
+            self.surface_vertex_buffer =
+                self.device
+                    .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("Surface Vertex Buffer"),
+                        contents: bytemuck::cast_slice(&iso.vertices),
+                        usage: wgpu::BufferUsages::VERTEX,
+                    });
+            self.surface_index_buffer =
+                self.device
+                    .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("Surface Index Buffer"),
+                        contents: bytemuck::cast_slice(&iso.indices),
+                        usage: wgpu::BufferUsages::INDEX,
+                    });
+            self.num_surface_indices = iso.indices.len() as u32;
+
+            // the extent moves with n and Z, and the reference lines with it
+            self.reference = ReferenceLines::new(self.wavefunction.extent() as f32);
+            self.reference_buffer =
+                self.device
+                    .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("Reference Buffer"),
+                        contents: bytemuck::cast_slice(&self.reference.vertices),
+                        usage: wgpu::BufferUsages::VERTEX,
+                    });
+
+            self.volume_uniform = VolumeUniform::new(
+                &self.wavefunction,
+                &self.camera,
+                self.transfer,
+                self.color_map,
+            );
+            self.update_slice();
+        }
+
+        let from = if generated.first {
+            // resampling the same state just swaps the cloud
+            if !new_state {
+                self.morph = None;
+            }
+            self.cloud = generated.cloud;
+            // the spin colouring may have been switched since it was requested
+            let spin_off = |view: SpinView| view == SpinView::Off;
+            self.spin_view = if spin_off(request.spin_view) == spin_off(self.requested.spin_view) {
+                self.requested.spin_view
+            } else {
+                request.spin_view
+            };
+            self.instance_capacity = request.count;
+            0
+        } else {
+            let from = self.cloud.len();
+            self.cloud.extend(generated.cloud);
+            from
+        };
+        // the density and radius maps scale to the whole cloud, so it is
+        // coloured again once all of it is in
+        if generated.first || generated.finished {
+            self.upload_cloud();
+        } else {
+            self.append_cloud(from);
+        }
+        if let Some(morph) = &mut self.morph {
+            morph.add(&self.instances[from..], now);
+        }
+    }
+
+    // writes the cloud part way through the morph, or as it ends up once
+    // the morph is over
+    fn update_morph(&mut self) {
+        let Some(morph) = &self.morph else {
+            return;
+        };
+        let now = Instant::now();
+        let over = morph.done(now, self.morph_seconds) && !self.generator.busy();
+        if over || self.morph_seconds <= 0. {
+            self.morph = None;
+            self.queue.write_buffer(
+                &self.instance_buffer,
+                0,
+                bytemuck::cast_slice(&self.instances),
+            );
+        } else {
+            let instances = morph.frame(&self.instances, now, self.morph_seconds);
+            self.queue
+                .write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(&instances));
+        }
+    }
+
+    fn upload_cloud(&mut self) {
+        self.instances = cloud::instances(
+            &self.cloud,
+            self.spin_view,
+            self.color_map,
+            particle_radius(&self.wavefunction, self.point_size),
+        );
+        self.instance_capacity = self.instance_capacity.max(self.instances.len());
+        if self.instance_buffer.size() != instance_bytes(self.instance_capacity) {
+            self.instance_buffer = instance_buffer(&self.device, self.instance_capacity);
+        }
+        self.queue.write_buffer(
+            &self.instance_buffer,
+            0,
+            bytemuck::cast_slice(&self.instances),
+        );
+        self.num_instances = self.instances.len() as u32;
+    }
+
+    // writes the particles from `from` on after those already uploaded
+    fn append_cloud(&mut self, from: usize) {
+        if self.cloud.len() > self.instance_capacity {
+            return self.upload_cloud();
+        }
+        let instances = cloud::instances(
+            &self.cloud[from..],
+            self.spin_view,
+            self.color_map,
+            particle_radius(&self.wavefunction, self.point_size),
+        );
+        self.queue.write_buffer(
+            &self.instance_buffer,
+            instance_bytes(from),
+            bytemuck::cast_slice(&instances),
+        );
+        self.instances.extend(instances);
+        self.num_instances = self.cloud.len() as u32;
+    }
+
+    /// false when the press landed on the panel rather than the scene
+    pub fn handle_click(&mut self, pressed: bool) -> bool {
+        let taken = self.show_panel && self.gui.mouse_button(pressed);
+        pressed && !taken
+    }
+
+    pub fn handle_cursor(&mut self, x: f64, y: f64) {
+        self.gui.cursor_moved(x, y);
+    }
+
     pub fn handle_mouse(&mut self, x: f64, y: f64) {
         self.camera_controller.turn(x, y);
     }
 
+    pub fn handle_pan(&mut self, x: f64, y: f64) {
+        self.camera_controller.pan(x, y);
+    }
+
+    pub fn handle_wheel(&mut self, delta: MouseScrollDelta) {
+        let lines = match delta {
+            MouseScrollDelta::LineDelta(_, y) => y,
+            MouseScrollDelta::PixelDelta(position) => (position.y / PIXELS_PER_LINE) as f32,
+        };
+        self.camera_controller.scroll(lines);
+    }
+
+    pub fn handle_pinch(&mut self, delta: f64) {
+        self.camera_controller.pinch(delta as f32);
+    }
+
+    pub fn handle_touch(&mut self, touch: Touch) {
+        let at = [touch.location.x, touch.location.y];
+        // the first finger down clicks, so it can work the panel too
+        let first = self.touches.count() == 0 && self.panel_finger.is_none();
+        if first && touch.phase == TouchPhase::Started {
+            self.handle_cursor(at[0], at[1]);
+            if !self.handle_click(true) {
+                self.panel_finger = Some(touch.id);
+                return;
+            }
+        }
+        if self.panel_finger == Some(touch.id) {
+            self.handle_cursor(at[0], at[1]);
+            if matches!(touch.phase, TouchPhase::Ended | TouchPhase::Cancelled) {
+                self.handle_click(false);
+                self.panel_finger = None;
+            }
+            return;
+        }
+
+        // moved the same as the mouse, which goes by logical pixels
+        let scale = self.window.scale_factor();
+        match self.touches.touch(touch.phase, touch.id, at) {
+            Gesture::None => (),
+            Gesture::Orbit { dx, dy } => self.handle_mouse(dx / scale, dy / scale),
+            Gesture::Pinch {
+                scale: spread,
+                dx,
+                dy,
+            } => {
+                self.handle_pinch(spread - 1.);
+                self.handle_pan(dx / scale, dy / scale);
+            }
+        }
+        if self.touches.count() == 0 {
+            self.handle_click(false);
+        }
+    }
+
     pub fn get_window(&self) -> &Window {
         &self.window
     }
@@ -279,17 +1478,46 @@ impl State {
         // reconfigure the surface
 
         self.configure_surface();
+        self.depth_view = depth::create_depth_view(&self.device, new_size);
         self.is_surface_configured = true;
     }
 
     pub fn update(&mut self) {
-        self.camera_controller.update_camera(&mut self.camera);
+        self.frame_timer.tick();
+        self.generator.work();
+        while let Some(generated) = self.generator.poll() {
+            self.apply(generated);
+        }
+        self.update_morph();
+        let dt = self.frame_timer.delta() as f32;
+        match &mut self.playing {
+            // paths loop, for leaving running
+            Some((path, time)) => {
+                *time += dt;
+                if *time > path.duration() {
+                    *time = (*time - path.duration()).min(path.duration());
+                }
+                path.at(*time).apply(&mut self.camera);
+            }
+            None => self.camera_controller.update_camera(&mut self.camera, dt),
+        }
         self.camera_uniform.update_view_proj(&self.camera);
+        self.camera_uniform.update_clip_planes(&self.clip_planes);
+        self.update_scale_bar();
         self.queue.write_buffer(
             &self.camera_buffer,
             0,
             bytemuck::cast_slice(&[self.camera_uniform]),
         );
+        self.update_hud();
+        if self.render_mode == RenderMode::Volume {
+            self.volume_uniform.update_camera(&self.camera);
+            self.queue.write_buffer(
+                &self.volume_buffer,
+                0,
+                bytemuck::cast_slice(&[self.volume_uniform]),
+            );
+        }
     }
 
     pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
@@ -313,6 +1541,11 @@ impl State {
                 ..Default::default()
             });
 
+        let background = match self.render_mode {
+            RenderMode::Mesh => wgpu::Color::WHITE,
+            RenderMode::Billboard | RenderMode::Volume => DARK_BACKGROUND,
+        };
+
         // device command buffer
         let mut encoder = self.device.create_command_encoder(&Default::default());
         // Create the renderpass which will clear the screen.
@@ -323,21 +1556,101 @@ impl State {
                 depth_slice: None,
                 resolve_target: None,
                 ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color::WHITE),
+                    load: wgpu::LoadOp::Clear(background),
                     store: wgpu::StoreOp::Store,
                 },
             })],
-            depth_stencil_attachment: None,
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
             timestamp_writes: None,
             occlusion_query_set: None,
         });
 
-        renderpass.set_pipeline(&self.render_pipeline); // 2.
+        // the volume is a backdrop, the opaque geometry is drawn over it
+        if self.render_mode == RenderMode::Volume {
+            renderpass.set_pipeline(&self.volume_pipeline);
+            renderpass.set_bind_group(0, &self.camera_bind_group, &[]);
+            renderpass.set_bind_group(1, &self.volume_bind_group, &[]);
+            renderpass.draw(0..3, 0..1);
+        }
+
         renderpass.set_bind_group(0, &self.camera_bind_group, &[]);
-        renderpass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-        renderpass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        // opaque geometry goes first so the see-through sprites are depth
+        // tested against all of it
+        if self.show_surface && self.num_surface_indices > 0 {
+            renderpass.set_pipeline(&self.surface_pipeline);
+            renderpass.set_vertex_buffer(0, self.surface_vertex_buffer.slice(..));
+            renderpass.set_index_buffer(
+                self.surface_index_buffer.slice(..),
+                wgpu::IndexFormat::Uint32,
+            );
+            renderpass.draw_indexed(0..self.num_surface_indices, 0, 0..1);
+        }
+        renderpass.set_pipeline(&self.line_pipeline);
+        renderpass.set_vertex_buffer(0, self.reference_buffer.slice(..));
+        for (show, range) in [
+            (self.show_axes, &self.reference.axes),
+            (self.show_grid, &self.reference.grid),
+            (self.show_nucleus, &self.reference.nucleus),
+        ] {
+            if show {
+                renderpass.draw(range.clone(), 0..1);
+            }
+        }
+        if self.show_flow && self.num_flow_vertices > 0 {
+            renderpass.set_pipeline(&self.line_pipeline);
+            renderpass.set_vertex_buffer(0, self.flow_buffer.slice(..));
+            renderpass.draw(0..self.num_flow_vertices, 0..1);
+        }
 
-        renderpass.draw_indexed(0..self.num_indices, 0, 0..1);
+        match self.render_mode {
+            RenderMode::Mesh => {
+                renderpass.set_pipeline(&self.render_pipeline); // 2.
+                renderpass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+                renderpass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+                renderpass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                renderpass.draw_indexed(0..self.num_indices, 0, 0..self.num_instances);
+            }
+            RenderMode::Billboard => {
+                // one four vertex strip per particle, the corners come from
+                // the vertex index
+                renderpass.set_pipeline(&self.billboard_pipeline);
+                renderpass.set_vertex_buffer(0, self.instance_buffer.slice(..));
+                renderpass.draw(0..4, 0..self.num_instances);
+            }
+            RenderMode::Volume => {}
+        }
+
+        if self.show_slice {
+            renderpass.set_pipeline(&self.slice_pipeline);
+            renderpass.set_bind_group(0, &self.camera_bind_group, &[]);
+            renderpass.set_bind_group(1, &self.slice_bind_group, &[]);
+            renderpass.draw(0..4, 0..1);
+        }
+        if self.show_scale_bar {
+            renderpass.set_pipeline(&self.overlay_line_pipeline);
+            renderpass.set_vertex_buffer(0, self.scale_bar_buffer.slice(..));
+            renderpass.draw(0..SCALE_BAR_VERTICES as u32, 0..1);
+        }
+        if self.num_legend_vertices > 0 {
+            renderpass.set_pipeline(&self.overlay_pipeline);
+            renderpass.set_vertex_buffer(0, self.legend_buffer.slice(..));
+            renderpass.draw(0..self.num_legend_vertices, 0..1);
+        }
+
+        // text goes over everything
+        if self.num_text_vertices > 0 {
+            renderpass.set_pipeline(&self.text_pipeline);
+            renderpass.set_bind_group(0, &self.text_bind_group, &[]);
+            renderpass.set_vertex_buffer(0, self.text_buffer.slice(..));
+            renderpass.draw(0..self.num_text_vertices, 0..1);
+        }
 
         drop(renderpass);
 
@@ -349,3 +1662,413 @@ impl State {
         Ok(())
     }
 }
+
+// small enough that the cloud stays see-through at every n, times the
+// panel's point size
+fn particle_radius(wavefunction: &Wavefunction, point_size: f32) -> f32 {
+    (PARTICLE_SCALE * wavefunction.extent()) as f32 * point_size
+}
+
+// the bookmarks saved by an earlier session, none if there weren't any
+fn load_bookmarks() -> Bookmarks {
+    #[cfg(not(target_arch = "wasm32"))]
+    let text = std::fs::read_to_string(BOOKMARK_FILE).ok();
+    #[cfg(target_arch = "wasm32")]
+    let text = local_storage().and_then(|storage| storage.get_item(BOOKMARK_FILE).ok().flatten());
+    match text.map(|text| Bookmarks::parse(&text)) {
+        Some(Ok(bookmarks)) => bookmarks,
+        Some(Err(e)) => {
+            log::error!("couldn't read {BOOKMARK_FILE}: {e:#}");
+            Bookmarks::default()
+        }
+        None => Bookmarks::default(),
+    }
+}
+
+// where the web build keeps what natively goes to files, for as long as the
+// browser keeps the site's data
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+fn bookmark_slot(code: KeyCode) -> Option<usize> {
+    [
+        KeyCode::Digit0,
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+    ]
+    .iter()
+    .position(|&digit| digit == code)
+}
+
+fn instance_bytes(count: usize) -> wgpu::BufferAddress {
+    (count * std::mem::size_of::<Instance>()) as wgpu::BufferAddress
+}
+
+// the cloud's instances, written in as they are generated
+fn instance_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Instance Buffer"),
+        size: instance_bytes(capacity),
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+// room for at least `bytes` of text vertices, rounded up so it is rarely grown
+fn text_buffer(device: &wgpu::Device, bytes: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Text Buffer"),
+        size: bytes.max(1 << 16).next_power_of_two() as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+// pipeline for the flat coloured shader, shared by everything that isn't lit
+fn flat_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    topology: wgpu::PrimitiveTopology,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Flat Pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some("vs_main"),
+            buffers: &[Vertex::desc()],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: Some(depth::depth_state(true)),
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
+
+// lit, opaque triangles with their own normals. Back faces are kept, they
+// are what shows through a clip plane.
+fn surface_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Surface Pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some("vs_main"),
+            buffers: &[SurfaceVertex::desc()],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: Some(depth::depth_state(true)),
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
+
+// camera facing sprites summed with additive blending, the sum doesn't depend
+// on draw order so they need no sorting. They are depth tested against the
+// opaque geometry but don't write depth, or they would cut each other off.
+fn billboard_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+) -> wgpu::RenderPipeline {
+    let additive = wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::One,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    };
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Billboard Pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some("vs_main"),
+            buffers: &[Instance::desc()],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState {
+                    color: additive,
+                    alpha: additive,
+                }),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleStrip,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: Some(depth::depth_state(false)),
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
+
+// a single fullscreen triangle blended over the background, premultiplied
+// by the opacity the raymarch built up
+fn volume_pipeline(
+    device: &wgpu::Device,
+    bind_group_layouts: &[&wgpu::BindGroupLayout],
+    shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+) -> wgpu::RenderPipeline {
+    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Volume Pipeline Layout"),
+        bind_group_layouts,
+        push_constant_ranges: &[],
+    });
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Volume Pipeline"),
+        layout: Some(&layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some("vs_main"),
+            buffers: &[],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: Some(wgpu::DepthStencilState {
+            depth_compare: wgpu::CompareFunction::Always,
+            ..depth::depth_state(false)
+        }),
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
+
+// the slice heatmap, a textured quad in screen space
+fn slice_pipeline(
+    device: &wgpu::Device,
+    bind_group_layouts: &[&wgpu::BindGroupLayout],
+    shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+) -> wgpu::RenderPipeline {
+    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Slice Pipeline Layout"),
+        bind_group_layouts,
+        push_constant_ranges: &[],
+    });
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Slice Pipeline"),
+        layout: Some(&layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some("vs_main"),
+            buffers: &[],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleStrip,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: Some(wgpu::DepthStencilState {
+            depth_compare: wgpu::CompareFunction::Always,
+            ..depth::depth_state(false)
+        }),
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
+
+// clip space geometry drawn over everything else
+fn overlay_pipeline(
+    device: &wgpu::Device,
+    shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    topology: wgpu::PrimitiveTopology,
+) -> wgpu::RenderPipeline {
+    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Overlay Pipeline Layout"),
+        bind_group_layouts: &[],
+        push_constant_ranges: &[],
+    });
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Overlay Pipeline"),
+        layout: Some(&layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some("vs_main"),
+            buffers: &[Vertex::desc()],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        // the pass has a depth attachment, but overlays ignore it
+        depth_stencil: Some(wgpu::DepthStencilState {
+            depth_compare: wgpu::CompareFunction::Always,
+            ..depth::depth_state(false)
+        }),
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
+
+// glyphs and panels blended over everything by their coverage
+fn text_pipeline(
+    device: &wgpu::Device,
+    bind_group_layout: &wgpu::BindGroupLayout,
+    shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+) -> wgpu::RenderPipeline {
+    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Text Pipeline Layout"),
+        bind_group_layouts: &[bind_group_layout],
+        push_constant_ranges: &[],
+    });
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Text Pipeline"),
+        layout: Some(&layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some("vs_main"),
+            buffers: &[TextVertex::desc()],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: Some(wgpu::DepthStencilState {
+            depth_compare: wgpu::CompareFunction::Always,
+            ..depth::depth_state(false)
+        }),
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
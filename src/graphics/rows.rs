@@ -0,0 +1,46 @@
+use anyhow::{Context, bail};
+
+/// the rows of numbers in a text file the camera is read from, with the
+/// line each was on. Blank lines and lines starting with '#' are skipped,
+/// and every other line has to hold `N` numbers, which `what` names for the
+/// error when it doesn't.
+pub fn parse_rows<const N: usize>(
+    text: &str,
+    what: &str,
+) -> anyhow::Result<Vec<(usize, [f32; N])>> {
+    let mut rows = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let numbers = line
+            .split_whitespace()
+            .map(|token| {
+                token
+                    .parse::<f32>()
+                    .with_context(|| format!("line {}: expected a number, got {token:?}", i + 1))
+            })
+            .collect::<anyhow::Result<Vec<f32>>>()?;
+        let Ok(row) = <[f32; N]>::try_from(numbers) else {
+            bail!("line {}: expected {what}", i + 1);
+        };
+        rows.push((i + 1, row));
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_rows;
+
+    #[test]
+    fn test_rows() {
+        let rows = parse_rows::<2>("# x y\n\n  1 2\n3 -4.5  \n", "x and y").unwrap();
+        assert_eq!(rows, [(3, [1., 2.]), (4, [3., -4.5])]);
+
+        let error = parse_rows::<2>("1 2\n3\n", "x and y").unwrap_err();
+        assert_eq!(error.to_string(), "line 2: expected x and y");
+        assert!(parse_rows::<2>("1 two", "x and y").is_err());
+    }
+}
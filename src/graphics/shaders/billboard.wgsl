@@ -0,0 +1,76 @@
+// Vertex shader
+struct CameraUniform {
+    eye: vec3<f32>,
+    aspect: f32,
+    view_proj: mat4x4<f32>,
+    right: vec3<f32>,
+    up: vec3<f32>,
+    clip_planes: array<vec4<f32>, 4>,
+    num_clip_planes: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+// hidden on the side each plane's normal points to
+fn clipped(p: vec3<f32>) -> bool {
+    for (var i = 0u; i < camera.num_clip_planes; i++) {
+        let plane = camera.clip_planes[i];
+        if dot(plane.xyz, p) > plane.w {
+            return true;
+        }
+    }
+    return false;
+}
+
+// same layout as the mesh instances, there is no per vertex buffer
+struct InstanceInput {
+    @location(2) position: vec3<f32>,
+    @location(3) radius: f32,
+    @location(4) color: vec3<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) corner: vec2<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+// the sprite is wider than the mesh particle, most of it is the faint tail
+const SPRITE_SCALE: f32 = 4.;
+// how much light a particle adds at its centre, the cloud builds up from
+// thousands of these
+const INTENSITY: f32 = 0.12;
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) index: u32,
+    instance: InstanceInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    // triangle strip over the corners (-1, -1), (1, -1), (-1, 1), (1, 1)
+    let corner = vec2(f32(index & 1u), f32(index >> 1u)) * 2. - 1.;
+    let size = instance.radius * SPRITE_SCALE;
+    let world_position = instance.position
+        + (camera.right * corner.x + camera.up * corner.y) * size;
+    out.clip_position = camera.view_proj * vec4<f32>(world_position, 1.0);
+    out.corner = corner;
+    out.color = vec4(instance.color, 1.);
+    // clipped particles go as a whole, collapsed to a point off screen
+    if clipped(instance.position) {
+        out.clip_position = vec4(2., 2., 2., 1.);
+    }
+    return out;
+}
+
+// Fragment shaders
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let r2 = dot(in.corner, in.corner);
+    if r2 > 1. {
+        discard;
+    }
+    // gaussian with σ = 1/3 of the sprite, cut off at 3σ
+    let falloff = exp(-4.5 * r2) * INTENSITY;
+    return in.color * falloff;
+}
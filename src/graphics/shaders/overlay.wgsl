@@ -0,0 +1,27 @@
+// Vertex shader
+// overlay geometry is already in clip space, so there is no camera
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec3<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(model.position.xy, 0.0, 1.0);
+    out.color = vec4(model.color, 1.);
+    return out;
+}
+
+// Fragment shaders
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
@@ -4,16 +4,37 @@ struct CameraUniform {
     eye: vec3<f32>,
     aspect: f32,
     view_proj: mat4x4<f32>,
+    right: vec3<f32>,
+    up: vec3<f32>,
+    clip_planes: array<vec4<f32>, 4>,
+    num_clip_planes: u32,
 };
 
 @group(0) @binding(0)
 var<uniform> camera: CameraUniform;
 
+// hidden on the side each plane's normal points to
+fn clipped(p: vec3<f32>) -> bool {
+    for (var i = 0u; i < camera.num_clip_planes; i++) {
+        let plane = camera.clip_planes[i];
+        if dot(plane.xyz, p) > plane.w {
+            return true;
+        }
+    }
+    return false;
+}
+
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) color: vec3<f32>,
 };
 
+struct InstanceInput {
+    @location(2) position: vec3<f32>,
+    @location(3) radius: f32,
+    @location(4) color: vec3<f32>,
+};
+
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>, // these get transformed
     @location(0) coord_position: vec3<f32>,
@@ -23,12 +44,19 @@ struct VertexOutput {
 @vertex
 fn vs_main(
     model: VertexInput,
+    instance: InstanceInput,
 ) -> VertexOutput {
 
     var out: VertexOutput;
-    out.clip_position = camera.view_proj * vec4<f32>(model.position, 1.0); // 2.
+    let world_position = model.position * instance.radius + instance.position;
+    out.clip_position = camera.view_proj * vec4<f32>(world_position, 1.0); // 2.
+    // shading works off the unit mesh, so every particle is lit the same way
     out.coord_position = model.position;
-    out.color = vec4(model.color, 1.);
+    out.color = vec4(instance.color, 1.);
+    // clipped particles go as a whole, collapsed to a point off screen
+    if clipped(instance.position) {
+        out.clip_position = vec4(2., 2., 2., 1.);
+    }
 
     return out;
 }
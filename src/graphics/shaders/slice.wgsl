@@ -0,0 +1,45 @@
+// Vertex shader
+struct CameraUniform {
+    eye: vec3<f32>,
+    aspect: f32,
+    view_proj: mat4x4<f32>,
+    right: vec3<f32>,
+    up: vec3<f32>,
+    clip_planes: array<vec4<f32>, 4>,
+    num_clip_planes: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0)
+var slice_texture: texture_2d<f32>;
+@group(1) @binding(1)
+var slice_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// the panel sits in the bottom left corner, square whatever the window shape
+const MARGIN: f32 = 0.05;
+const HEIGHT: f32 = 0.7;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    // triangle strip over the corners (0, 0), (1, 0), (0, 1), (1, 1)
+    let corner = vec2(f32(index & 1u), f32(index >> 1u));
+    let size = vec2(HEIGHT / camera.aspect, HEIGHT);
+    let position = vec2(-1. + MARGIN / camera.aspect, -1. + MARGIN) + corner * size;
+    out.clip_position = vec4(position, 0., 1.);
+    out.uv = vec2(corner.x, 1. - corner.y);
+    return out;
+}
+
+// Fragment shaders
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(slice_texture, slice_sampler, in.uv);
+}
@@ -0,0 +1,65 @@
+// Vertex shader
+struct CameraUniform {
+    eye: vec3<f32>,
+    aspect: f32,
+    view_proj: mat4x4<f32>,
+    right: vec3<f32>,
+    up: vec3<f32>,
+    clip_planes: array<vec4<f32>, 4>,
+    num_clip_planes: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+// hidden on the side each plane's normal points to
+fn clipped(p: vec3<f32>) -> bool {
+    for (var i = 0u; i < camera.num_clip_planes; i++) {
+        let plane = camera.clip_planes[i];
+        if dot(plane.xyz, p) > plane.w {
+            return true;
+        }
+    }
+    return false;
+}
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) color: vec3<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) world_position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) color: vec4<f32>,
+};
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * vec4<f32>(model.position, 1.0);
+    out.world_position = model.position;
+    out.normal = model.normal;
+    out.color = vec4(model.color, 1.);
+    return out;
+}
+
+// Fragment shaders
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    if clipped(in.world_position) {
+        discard;
+    }
+    // a headlight at the camera, so the side facing the viewer is always lit.
+    // Lit from both sides, a clip plane shows the inside of the surface.
+    let normal = normalize(in.normal);
+    let to_eye = normalize(camera.eye - in.world_position);
+    let diffuse = abs(dot(normal, to_eye));
+    let specular = pow(diffuse, 40.) * 0.3;
+    let lit = in.color.rgb * (0.25 + 0.75 * diffuse) + vec3(specular);
+    return vec4(lit, 1.);
+}
@@ -0,0 +1,145 @@
+// the layout matches VolumeUniform in volume.rs
+struct VolumeUniform {
+    inv_view_proj: mat4x4<f32>,
+    eye: vec3<f32>,
+    extent: f32,
+    radial: array<vec4<f32>, 4>,
+    angular: array<vec4<f32>, 4>,
+    colors: array<vec4<f32>, 16>,
+    radial_scale: f32,
+    l: f32,
+    m: f32,
+    sin_power: f32,
+    max_density: f32,
+    gain: f32,
+    gamma: f32,
+    sign: f32,
+};
+
+struct CameraUniform {
+    eye: vec3<f32>,
+    aspect: f32,
+    view_proj: mat4x4<f32>,
+    right: vec3<f32>,
+    up: vec3<f32>,
+    clip_planes: array<vec4<f32>, 4>,
+    num_clip_planes: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0)
+var<uniform> volume: VolumeUniform;
+
+// hidden on the side each plane's normal points to
+fn clipped(p: vec3<f32>) -> bool {
+    for (var i = 0u; i < camera.num_clip_planes; i++) {
+        let plane = camera.clip_planes[i];
+        if dot(plane.xyz, p) > plane.w {
+            return true;
+        }
+    }
+    return false;
+}
+
+const STEPS: i32 = 256;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) ndc: vec2<f32>,
+};
+
+// Vertex shader
+// one triangle that covers the whole screen
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let ndc = vec2(f32(index & 1u) * 4. - 1., f32(index >> 1u) * 4. - 1.);
+    out.clip_position = vec4(ndc, 0., 1.);
+    out.ndc = ndc;
+    return out;
+}
+
+// Σ c_i x^i by Horner's rule
+fn poly(c: array<vec4<f32>, 4>, x: f32) -> f32 {
+    var sum = 0.;
+    for (var i = 15; i >= 0; i--) {
+        sum = sum * x + c[i / 4][i % 4];
+    }
+    return sum;
+}
+
+// x^k, with 0^0 = 1 which pow doesn't promise
+fn power(x: f32, k: f32) -> f32 {
+    return select(pow(x, k), 1., k == 0.);
+}
+
+// ψ without the e^(imφ), and cos(mφ) for the sign of its real part
+fn amplitude(p: vec3<f32>) -> vec2<f32> {
+    let r = length(p);
+    let rho = r * volume.radial_scale;
+    let radial = poly(volume.radial, rho) * exp(-rho / 2.) * power(rho, volume.l);
+    let cos_t = select(1., p.z / r, r > 0.);
+    let sin_t = sqrt(max(1. - cos_t * cos_t, 0.));
+    let angular = poly(volume.angular, cos_t) * power(sin_t, volume.sin_power);
+    return vec2(radial * angular, cos(volume.m * atan2(p.y, p.x)));
+}
+
+// Fragment shaders
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // the pixel's ray runs from the near plane to the far one, which works
+    // for either projection
+    let near = volume.inv_view_proj * vec4(in.ndc, 0., 1.);
+    let far = volume.inv_view_proj * vec4(in.ndc, 1., 1.);
+    let origin = near.xyz / near.w;
+    let dir = normalize(far.xyz / far.w - origin);
+
+    // only march through the sphere the orbital fits in
+    let b = dot(origin, dir);
+    let c = dot(origin, origin) - volume.extent * volume.extent;
+    let disc = b * b - c;
+    if disc <= 0. {
+        discard;
+    }
+    let t0 = max(-b - sqrt(disc), 0.);
+    let t1 = -b + sqrt(disc);
+    if t1 <= t0 {
+        discard;
+    }
+    let dt = (t1 - t0) / f32(STEPS);
+    // opacity is per a0, so the look doesn't depend on the step count
+    let gain = volume.gain * dt;
+
+    // front to back, premultiplied
+    var color = vec3(0.);
+    var alpha = 0.;
+    for (var i = 0; i < STEPS; i++) {
+        let p = origin + dir * (t0 + (f32(i) + 0.5) * dt);
+        if clipped(p) {
+            continue;
+        }
+        let psi = amplitude(p);
+        let density = psi.x * psi.x / volume.max_density;
+        // the colour table follows the same curve as the opacity
+        let value = pow(min(density, 1.), volume.gamma);
+        let a = 1. - exp(-gain * value);
+
+        var sample_color: vec3<f32>;
+        if volume.sign > 0.5 {
+            sample_color = select(volume.colors[0].rgb, volume.colors[15].rgb, psi.x * psi.y >= 0.);
+        } else {
+            let x = value * 15.;
+            let k = min(i32(x), 14);
+            sample_color = mix(volume.colors[k].rgb, volume.colors[k + 1].rgb, x - f32(k));
+        }
+
+        color += (1. - alpha) * a * sample_color;
+        alpha += (1. - alpha) * a;
+        if alpha > 0.99 {
+            break;
+        }
+    }
+    return vec4(color, alpha);
+}
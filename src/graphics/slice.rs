@@ -0,0 +1,114 @@
+use super::colormap::{Gradient, to_srgb8};
+use crate::particle_gen::{Orbital, cartesian_to_spherical};
+use cgmath::*;
+
+// the camera uniform has room for this many
+pub const MAX_CLIP_PLANES: usize = 4;
+pub const SLICE_RESO: u32 = 128;
+
+/// everything on the side the normal points to, where normal · p > offset,
+/// is hidden
+#[derive(Copy, Clone, Debug)]
+pub struct ClipPlane {
+    pub normal: Vector3<f32>,
+    pub offset: f32,
+}
+
+impl ClipPlane {
+    /// the plane through the origin that cuts away the half facing the eye
+    pub fn facing(eye: Point3<f32>) -> ClipPlane {
+        ClipPlane {
+            normal: eye.to_vec().normalize(),
+            offset: 0.,
+        }
+    }
+
+    pub fn equation(&self) -> [f32; 4] {
+        [self.normal.x, self.normal.y, self.normal.z, self.offset]
+    }
+
+    // two unit vectors spanning the plane
+    fn basis(&self) -> (Vector3<f32>, Vector3<f32>) {
+        let helper = if self.normal.z.abs() < 0.9 {
+            Vector3::unit_z()
+        } else {
+            Vector3::unit_x()
+        };
+        let u = helper.cross(self.normal).normalize();
+        (u, self.normal.cross(u))
+    }
+}
+
+impl Default for ClipPlane {
+    // the xz plane, which holds the z axis every orbital is aligned to
+    fn default() -> Self {
+        ClipPlane {
+            normal: Vector3::unit_y(),
+            offset: 0.,
+        }
+    }
+}
+
+/// |ψ|² over a square of the plane 2 extent wide, as SLICE_RESO² sRGB
+/// pixels. It is scaled to the orbital's densest point rather than the
+/// slice's, so a nodal plane stays dark, and square rooted so the weaker
+/// lobes still show.
+pub fn heatmap<O: Orbital>(
+    orbital: &O,
+    plane: &ClipPlane,
+    extent: f64,
+    max_density: f64,
+    gradient: Gradient,
+) -> Vec<[u8; 4]> {
+    let (u, v) = plane.basis();
+    let centre = plane.normal * plane.offset;
+    let reso = SLICE_RESO as usize;
+    let mut density = Vec::with_capacity(reso * reso);
+    // row 0 is the top of the texture
+    for row in 0..reso {
+        let b = (1. - 2. * (row as f64 + 0.5) / reso as f64) * extent;
+        for col in 0..reso {
+            let a = (2. * (col as f64 + 0.5) / reso as f64 - 1.) * extent;
+            let p = centre + u * a as f32 + v * b as f32;
+            let (r, theta, phi) = cartesian_to_spherical(p.x as f64, p.y as f64, p.z as f64);
+            density.push(orbital.density(r, theta, phi));
+        }
+    }
+    density
+        .into_iter()
+        .map(|d| to_srgb8(gradient.sample((d / max_density).sqrt())))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ClipPlane, heatmap};
+    use crate::graphics::colormap::{Gradient, to_srgb8};
+    use crate::graphics::volume::max_density;
+    use crate::particle_gen::wavefunction::Wavefunction;
+    use cgmath::Vector3;
+
+    #[test]
+    fn test_heatmap() {
+        let state = Wavefunction::new(2, 1, 0, 1.);
+        let dark = to_srgb8(Gradient::Magma.sample(0.));
+        let max = max_density(&state);
+
+        // the xy plane is the nodal plane of 2p_z
+        let nodal = ClipPlane {
+            normal: Vector3::unit_z(),
+            offset: 0.,
+        };
+        let pixels = heatmap(&state, &nodal, 15., max, Gradient::Magma);
+        assert!(pixels.iter().all(|p| *p == dark));
+
+        // the xz plane cuts through both lobes, the brightest pixels are on
+        // the z axis, the middle column
+        let pixels = heatmap(&state, &ClipPlane::default(), 15., max, Gradient::Magma);
+        let brightest = (0..pixels.len())
+            .max_by_key(|&i| pixels[i].iter().map(|&c| c as u32).sum::<u32>())
+            .unwrap();
+        let col = brightest % super::SLICE_RESO as usize;
+        assert!(col.abs_diff(64) <= 1, "{col}");
+    }
+}
@@ -0,0 +1,221 @@
+use super::font::{GLYPH_HEIGHT, GLYPH_WIDTH, GLYPHS, glyph_index};
+
+/* NOTE:
+    Text is drawn from a single coverage texture holding every glyph of the
+    bitmap font, one glyph per cell with a blank column and row between them
+    so nearest sampling never bleeds into a neighbour. After the glyphs comes
+    one solid cell, which lets plain rectangles go through the same pipeline.
+
+    Everything is laid out in whole window pixels, with glyphs scaled by a
+    whole number, so the font stays crisp at any window size.
+*/
+const ATLAS_COLUMNS: u32 = 16;
+const CELL_WIDTH: u32 = GLYPH_WIDTH + 1;
+const CELL_HEIGHT: u32 = GLYPH_HEIGHT + 1;
+// the solid cell after the glyphs
+const SOLID: usize = GLYPHS.len();
+const ATLAS_CELLS: u32 = GLYPHS.len() as u32 + 1;
+pub const ATLAS_WIDTH: u32 = ATLAS_COLUMNS * CELL_WIDTH;
+pub const ATLAS_HEIGHT: u32 = ATLAS_CELLS.div_ceil(ATLAS_COLUMNS) * CELL_HEIGHT;
+
+/// one byte of coverage per texel, row 0 at the top
+pub fn atlas() -> Vec<u8> {
+    let mut texels = vec![0; (ATLAS_WIDTH * ATLAS_HEIGHT) as usize];
+    let solid = [(1 << GLYPH_WIDTH) - 1; GLYPH_HEIGHT as usize];
+    for (cell, rows) in GLYPHS.iter().chain([&solid]).enumerate() {
+        let (x0, y0) = cell_origin(cell);
+        for (row, bits) in (0..).zip(rows) {
+            for col in 0..GLYPH_WIDTH {
+                if bits >> (GLYPH_WIDTH - 1 - col) & 1 == 1 {
+                    texels[((y0 + row) * ATLAS_WIDTH + x0 + col) as usize] = 255;
+                }
+            }
+        }
+    }
+    texels
+}
+
+fn cell_origin(cell: usize) -> (u32, u32) {
+    let cell = cell as u32;
+    (
+        cell % ATLAS_COLUMNS * CELL_WIDTH,
+        cell / ATLAS_COLUMNS * CELL_HEIGHT,
+    )
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct TextVertex {
+    pub position: [f32; 2],
+    pub uv: [f32; 2],
+    pub color: [f32; 4],
+}
+
+impl TextVertex {
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<TextVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+            ],
+        }
+    }
+}
+
+/// TriangleList quads of text and rectangles for one frame, positioned in
+/// window pixels from the top left
+pub struct TextBatch {
+    width: f32,
+    height: f32,
+    scale: f32,
+    pub vertices: Vec<TextVertex>,
+}
+
+impl TextBatch {
+    pub fn new(size: winit::dpi::PhysicalSize<u32>, scale: u32) -> TextBatch {
+        TextBatch {
+            width: size.width.max(1) as f32,
+            height: size.height.max(1) as f32,
+            scale: scale.max(1) as f32,
+            vertices: Vec::new(),
+        }
+    }
+
+    /// distance between the tops of two lines
+    pub fn line_height(&self) -> f32 {
+        CELL_HEIGHT as f32 * self.scale
+    }
+
+    /// width and height of `text` in pixels, lines are split on '\n'
+    pub fn measure(&self, text: &str) -> [f32; 2] {
+        let columns = text.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+        let lines = text.lines().count();
+        [
+            columns as f32 * CELL_WIDTH as f32 * self.scale,
+            lines as f32 * self.line_height(),
+        ]
+    }
+
+    /// window pixels of a point in clip space
+    pub fn to_pixels(&self, clip: [f32; 2]) -> [f32; 2] {
+        [
+            (clip[0] + 1.) / 2. * self.width,
+            (1. - clip[1]) / 2. * self.height,
+        ]
+    }
+
+    /// `text` with its top left corner at `at`
+    pub fn text(&mut self, text: &str, at: [f32; 2], color: [f32; 4]) {
+        let [x0, mut y] = at.map(f32::round);
+        for line in text.lines() {
+            let mut x = x0;
+            for c in line.chars() {
+                if c != ' ' {
+                    self.cell(glyph_index(c), [x, y], color);
+                }
+                x += CELL_WIDTH as f32 * self.scale;
+            }
+            y += self.line_height();
+        }
+    }
+
+    /// a filled rectangle between two corners
+    pub fn rect(&mut self, min: [f32; 2], max: [f32; 2], color: [f32; 4]) {
+        // sampled from the middle of the solid cell so the edges stay solid
+        let (x, y) = cell_origin(SOLID);
+        let uv = [
+            (x as f32 + GLYPH_WIDTH as f32 / 2.) / ATLAS_WIDTH as f32,
+            (y as f32 + GLYPH_HEIGHT as f32 / 2.) / ATLAS_HEIGHT as f32,
+        ];
+        self.quad(min, max, [uv, uv], color);
+    }
+
+    /// a rectangle drawn under everything added since vertex `from`, for
+    /// backgrounds whose size is only known once their contents are laid out
+    pub fn rect_behind(&mut self, from: usize, min: [f32; 2], max: [f32; 2], color: [f32; 4]) {
+        self.rect(min, max, color);
+        let added = self.vertices.len() - 6;
+        self.vertices[from.min(added)..].rotate_right(6);
+    }
+
+    fn cell(&mut self, cell: usize, at: [f32; 2], color: [f32; 4]) {
+        let (x, y) = cell_origin(cell);
+        let uv_min = [
+            x as f32 / ATLAS_WIDTH as f32,
+            y as f32 / ATLAS_HEIGHT as f32,
+        ];
+        let uv_max = [
+            (x + GLYPH_WIDTH) as f32 / ATLAS_WIDTH as f32,
+            (y + GLYPH_HEIGHT) as f32 / ATLAS_HEIGHT as f32,
+        ];
+        let max = [
+            at[0] + GLYPH_WIDTH as f32 * self.scale,
+            at[1] + GLYPH_HEIGHT as f32 * self.scale,
+        ];
+        self.quad(at, max, [uv_min, uv_max], color);
+    }
+
+    fn quad(&mut self, min: [f32; 2], max: [f32; 2], uv: [[f32; 2]; 2], color: [f32; 4]) {
+        let clip = |x: f32, y: f32| [2. * x / self.width - 1., 1. - 2. * y / self.height];
+        let [[u0, v0], [u1, v1]] = uv;
+        for (x, y, u, v) in [
+            (min[0], min[1], u0, v0),
+            (min[0], max[1], u0, v1),
+            (max[0], max[1], u1, v1),
+            (min[0], min[1], u0, v0),
+            (max[0], max[1], u1, v1),
+            (max[0], min[1], u1, v0),
+        ] {
+            self.vertices.push(TextVertex {
+                position: clip(x, y),
+                uv: [u, v],
+                color,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ATLAS_HEIGHT, ATLAS_WIDTH, TextBatch, atlas};
+    use crate::graphics::font::glyph_index;
+
+    #[test]
+    fn test_text_layout() {
+        let texels = atlas();
+        assert_eq!(texels.len(), (ATLAS_WIDTH * ATLAS_HEIGHT) as usize);
+        // 'I' has its top bar in columns 1 to 3 of its cell
+        let i = glyph_index('I') as u32;
+        let (x, y) = (i % 16 * 6, i / 16 * 8);
+        let top: Vec<u8> = (0..5)
+            .map(|c| texels[(y * ATLAS_WIDTH + x + c) as usize])
+            .collect();
+        assert_eq!(top, [0, 255, 255, 255, 0]);
+        assert_eq!(glyph_index('ψ'), 95);
+        assert_eq!(glyph_index('€'), glyph_index('?'));
+
+        let mut batch = TextBatch::new(winit::dpi::PhysicalSize::new(800, 600), 2);
+        assert_eq!(batch.measure("n = 2\nE = -3.40 eV"), [12. * 12., 2. * 16.]);
+        // spaces take room but draw nothing
+        batch.text("a b", [0., 0.], [1.; 4]);
+        assert_eq!(batch.vertices.len(), 12);
+        // the top left pixel is the top left of clip space
+        assert_eq!(batch.vertices[0].position, [-1., 1.]);
+        assert_eq!(batch.to_pixels([1., -1.]), [800., 600.]);
+    }
+}
@@ -0,0 +1,137 @@
+use winit::event::TouchPhase;
+
+/* NOTE:
+    Touch screens send each finger on its own, so the fingers down are kept
+    here and every move is turned into what the mouse would have done:
+
+        one finger     drag to orbit
+        two fingers    pinch to zoom, move together to pan
+
+    Only the first two fingers count, and each move is measured from where
+    that finger last was, so putting a finger down or lifting one never
+    makes the view jump.
+*/
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Gesture {
+    None,
+    Orbit { dx: f64, dy: f64 },
+    // `scale` is the new spread of the fingers over the old, and dx, dy how
+    // far their midpoint moved
+    Pinch { scale: f64, dx: f64, dy: f64 },
+}
+
+#[derive(Default)]
+pub struct Touches {
+    // in the order they went down
+    fingers: Vec<(u64, [f64; 2])>,
+}
+
+impl Touches {
+    pub fn count(&self) -> usize {
+        self.fingers.len()
+    }
+
+    /// follows finger `id` to `at`, in pixels, and says what it did
+    pub fn touch(&mut self, phase: TouchPhase, id: u64, at: [f64; 2]) -> Gesture {
+        let index = self.fingers.iter().position(|&(finger, _)| finger == id);
+        match (phase, index) {
+            (TouchPhase::Started, None) => {
+                self.fingers.push((id, at));
+                Gesture::None
+            }
+            (TouchPhase::Moved, Some(i)) if i < 2 => {
+                let before = self.fingers[i].1;
+                let (middle, spread) = self.pair();
+                self.fingers[i].1 = at;
+                match self.fingers.len() {
+                    1 => Gesture::Orbit {
+                        dx: at[0] - before[0],
+                        dy: at[1] - before[1],
+                    },
+                    _ => {
+                        let (new_middle, new_spread) = self.pair();
+                        Gesture::Pinch {
+                            // fingers on top of each other can't be measured,
+                            // before or after
+                            scale: if spread > 0. && new_spread > 0. {
+                                new_spread / spread
+                            } else {
+                                1.
+                            },
+                            dx: new_middle[0] - middle[0],
+                            dy: new_middle[1] - middle[1],
+                        }
+                    }
+                }
+            }
+            (TouchPhase::Moved, Some(i)) => {
+                self.fingers[i].1 = at;
+                Gesture::None
+            }
+            (TouchPhase::Ended | TouchPhase::Cancelled, Some(i)) => {
+                self.fingers.remove(i);
+                Gesture::None
+            }
+            _ => Gesture::None,
+        }
+    }
+
+    // midpoint of and distance between the first two fingers
+    fn pair(&self) -> ([f64; 2], f64) {
+        let [(_, a), (_, b)] = [self.fingers[0], self.fingers[1.min(self.count() - 1)]];
+        let middle = [(a[0] + b[0]) / 2., (a[1] + b[1]) / 2.];
+        (middle, (a[0] - b[0]).hypot(a[1] - b[1]))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Gesture, Touches};
+    use winit::event::TouchPhase::*;
+
+    #[test]
+    fn test_touches() {
+        let mut touches = Touches::default();
+        assert_eq!(touches.touch(Started, 1, [10., 10.]), Gesture::None);
+        assert_eq!(
+            touches.touch(Moved, 1, [13., 6.]),
+            Gesture::Orbit { dx: 3., dy: -4. }
+        );
+
+        // a second finger doesn't move anything until one of them moves
+        assert_eq!(touches.touch(Started, 2, [23., 6.]), Gesture::None);
+        assert_eq!(
+            touches.touch(Moved, 2, [33., 6.]),
+            Gesture::Pinch {
+                scale: 2.,
+                dx: 5.,
+                dy: 0.
+            }
+        );
+        // nor does bringing them together onto one spot
+        assert_eq!(
+            touches.touch(Moved, 2, [13., 6.]),
+            Gesture::Pinch {
+                scale: 1.,
+                dx: -10.,
+                dy: 0.
+            }
+        );
+        touches.touch(Moved, 2, [33., 6.]);
+        // a third is ignored
+        assert_eq!(touches.touch(Started, 3, [0., 0.]), Gesture::None);
+        assert_eq!(touches.touch(Moved, 3, [50., 50.]), Gesture::None);
+
+        // lifting the first leaves the second to orbit from where it is
+        touches.touch(Ended, 1, [13., 6.]);
+        touches.touch(Cancelled, 3, [50., 50.]);
+        assert_eq!(touches.count(), 1);
+        assert_eq!(
+            touches.touch(Moved, 2, [34., 6.]),
+            Gesture::Orbit { dx: 1., dy: 0. }
+        );
+        // fingers never seen going down are ignored
+        assert_eq!(touches.touch(Moved, 9, [0., 0.]), Gesture::None);
+    }
+}
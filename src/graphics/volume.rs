@@ -0,0 +1,147 @@
+use super::camera::Camera;
+use super::colormap::{ColorMap, sign_color};
+use crate::particle_gen::Orbital;
+use crate::particle_gen::wavefunction::Wavefunction;
+use cgmath::SquareMatrix;
+
+// polynomial terms the shader has room for, enough for n and l up to 16
+pub const MAX_COEFFICIENTS: usize = 16;
+const COLOR_STOPS: usize = 16;
+// grid used to find the densest point, which the transfer function is
+// relative to
+const MAX_SEARCH_RESO: usize = 48;
+
+/// how density maps to light. Opacity per unit length is
+/// gain * (|ψ|² / max |ψ|²)^gamma, so lowering gamma brings out the faint
+/// outer shells.
+#[derive(Copy, Clone, Debug)]
+pub struct Transfer {
+    pub gain: f32,
+    pub gamma: f32,
+}
+
+impl Default for Transfer {
+    fn default() -> Self {
+        Transfer {
+            gain: 2.,
+            gamma: 0.5,
+        }
+    }
+}
+
+/* NOTE:
+    Everything the raymarching shader needs to evaluate ψ itself: the radial
+    and angular polynomials straight from `Wavefunction`, and the camera
+    inverted so each pixel can find its ray. The layout matches the WGSL
+    struct, every array element is a vec4 as uniform buffers require.
+*/
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct VolumeUniform {
+    inv_view_proj: [[f32; 4]; 4],
+    eye: [f32; 3],
+    extent: f32,
+    radial: [[f32; 4]; MAX_COEFFICIENTS / 4],
+    angular: [[f32; 4]; MAX_COEFFICIENTS / 4],
+    colors: [[f32; 4]; COLOR_STOPS],
+    radial_scale: f32,
+    l: f32,
+    m: f32,
+    sin_power: f32,
+    max_density: f32,
+    gain: f32,
+    gamma: f32,
+    // 1 colours by the sign of Re ψ instead of the colour table
+    sign: f32,
+}
+
+impl VolumeUniform {
+    pub fn new(
+        wavefunction: &Wavefunction,
+        camera: &Camera,
+        transfer: Transfer,
+        color_map: ColorMap,
+    ) -> VolumeUniform {
+        let pack = |c: Vec<f64>| {
+            assert!(
+                c.len() <= MAX_COEFFICIENTS,
+                "the volume shader only fits {MAX_COEFFICIENTS} polynomial terms"
+            );
+            let mut packed = [[0.; 4]; MAX_COEFFICIENTS / 4];
+            for (i, c) in c.into_iter().enumerate() {
+                packed[i / 4][i % 4] = c as f32;
+            }
+            packed
+        };
+
+        // the sign map shows up as red and blue lobes, anything else uses
+        // a gradient
+        let gradient = color_map.gradient();
+        let colors = std::array::from_fn(|i| {
+            let [r, g, b] = gradient.sample(i as f64 / (COLOR_STOPS - 1) as f64);
+            [r, g, b, 1.]
+        });
+        let [pr, pg, pb] = sign_color(1.);
+        let [nr, ng, nb] = sign_color(-1.);
+        let mut uniform = VolumeUniform {
+            inv_view_proj: cgmath::Matrix4::identity().into(),
+            eye: [0.; 3],
+            extent: wavefunction.extent() as f32,
+            radial: pack(wavefunction.radial_coefficients()),
+            angular: pack(wavefunction.angular_coefficients()),
+            colors,
+            radial_scale: (2. / (wavefunction.n() as f64 * wavefunction.a0())) as f32,
+            l: wavefunction.l() as f32,
+            m: wavefunction.m() as f32,
+            sin_power: if wavefunction.m() % 2 == 0 {
+                0.
+            } else {
+                wavefunction.m().abs() as f32
+            },
+            max_density: max_density(wavefunction) as f32,
+            gain: transfer.gain,
+            gamma: transfer.gamma,
+            sign: 0.,
+        };
+        if color_map == ColorMap::Sign {
+            // the two ends of the table hold the sign colours
+            uniform.sign = 1.;
+            uniform.colors[0] = [nr, ng, nb, 1.];
+            uniform.colors[COLOR_STOPS - 1] = [pr, pg, pb, 1.];
+        }
+        uniform.update_camera(camera);
+        uniform
+    }
+
+    pub fn update_camera(&mut self, camera: &Camera) {
+        let view_proj = camera.build_view_projection_matrix();
+        self.inv_view_proj = view_proj
+            .invert()
+            .unwrap_or(cgmath::Matrix4::identity())
+            .into();
+        self.eye = camera.eye.into();
+    }
+
+    pub fn set_transfer(&mut self, transfer: Transfer) {
+        self.gain = transfer.gain;
+        self.gamma = transfer.gamma;
+    }
+}
+
+/// the densest point on a grid over the orbital. The density is φ symmetric,
+/// so the grid only has to cover the xz half plane.
+pub fn max_density(wavefunction: &Wavefunction) -> f64 {
+    let extent = wavefunction.extent();
+    let mut max: f64 = 0.;
+    for i in 0..MAX_SEARCH_RESO {
+        let x = extent * i as f64 / MAX_SEARCH_RESO as f64;
+        for j in 0..=2 * MAX_SEARCH_RESO {
+            let z = extent * (j as f64 / MAX_SEARCH_RESO as f64 - 1.);
+            let r = (x * x + z * z).sqrt();
+            if r > 0. {
+                max = max.max(wavefunction.density(r, (z / r).acos(), 0.));
+            }
+        }
+    }
+    max
+}
@@ -1,2 +1,3 @@
+pub mod charts;
 pub mod graphics;
 pub mod particle_gen;
@@ -1,5 +1,4 @@
 use orblib::graphics::render::State;
-use orblib::*;
 use std::sync::Arc;
 use winit::{
     application::ApplicationHandler,
@@ -19,6 +18,7 @@ struct App {
     proxy: Option<winit::event_loop::EventLoopProxy<State>>,
 
     mouse_pressed: bool,
+    pan_pressed: bool,
 }
 
 impl App {
@@ -30,6 +30,7 @@ impl App {
             #[cfg(target_arch = "wasm32")]
             proxy,
             mouse_pressed: false,
+            pan_pressed: false,
         }
     }
 }
@@ -94,13 +95,13 @@ impl ApplicationHandler<State> for App {
             None => return,
         };
 
-        match event {
-            DeviceEvent::MouseMotion { delta } => {
-                if self.mouse_pressed {
-                    state.handle_mouse(delta.0, delta.1);
-                }
+        if let DeviceEvent::MouseMotion { delta } = event {
+            if self.mouse_pressed {
+                state.handle_mouse(delta.0, delta.1);
+            }
+            if self.pan_pressed {
+                state.handle_pan(delta.0, delta.1);
             }
-            _ => {}
         }
     }
 
@@ -136,9 +137,20 @@ impl ApplicationHandler<State> for App {
             } => state.handle_key(event_loop, code, key_state.is_pressed()),
             WindowEvent::MouseInput {
                 button: MouseButton::Left,
-                state,
+                state: button_state,
                 ..
-            } => self.mouse_pressed = state == ElementState::Pressed,
+            } => self.mouse_pressed = state.handle_click(button_state == ElementState::Pressed),
+            WindowEvent::MouseInput {
+                button: MouseButton::Right,
+                state: button_state,
+                ..
+            } => self.pan_pressed = button_state == ElementState::Pressed,
+            WindowEvent::MouseWheel { delta, .. } => state.handle_wheel(delta),
+            WindowEvent::PinchGesture { delta, .. } => state.handle_pinch(delta),
+            WindowEvent::Touch(touch) => state.handle_touch(touch),
+            WindowEvent::CursorMoved { position, .. } => {
+                state.handle_cursor(position.x, position.y)
+            }
             _ => (),
         }
     }
@@ -1,11 +1,11 @@
-const GRID_LIMIT: f64 = 1000.;
-const HALF_GRID_LIMIT: f64 = GRID_LIMIT / 2.;
-
-mod wavefunction {
-    use num::complex::ComplexFloat;
+pub mod wavefunction {
+    use super::Orbital;
+    use num::complex::{Complex64, ComplexFloat};
     use scilib::math::polynomial::Poly;
     use std::f64::consts::PI;
 
+    pub const RYDBERG_EV: f64 = 13.605_693_122_99;
+
     pub struct Wavefunction {
         n: i32,
         l: i32,
@@ -15,28 +15,34 @@ mod wavefunction {
         legendre_poly: Poly,
     }
 
-    fn factorial(n: i32) -> i32 {
+    // i64 holds up to 20!, so n + l can go up to 20
+    pub(crate) fn factorial(n: i32) -> i64 {
         if n == 0 {
             return 1;
         }
-        return factorial(n - 1) * n;
+        factorial(n - 1) * n as i64
+    }
+
+    /// P_l^|m|(cos θ) scaled so that it times e^(imφ) is the normalised Y_lm
+    pub(crate) fn legendre_poly(l: i32, m: i32) -> Poly {
+        let neg = if m % 2 == 0 { 1.0 } else { -1.0 };
+        let leg_coef = (neg)
+            * (((2 * l + 1) as i64 * factorial(l - (m.abs()))) as f64
+                / (4.0 * PI * factorial(l + m.abs()) as f64))
+                .sqrt();
+        // the normalisation above is for P_l^|m|, the sign of m only lives in e^(imφ)
+        Poly::gen_legendre(l.try_into().unwrap(), m.abs().try_into().unwrap()) * leg_coef
     }
 
     impl Wavefunction {
         pub fn new(n: i32, l: i32, m: i32, a0: f64) -> Wavefunction {
             let n_f = n as f64;
             let lag_coef = f64::sqrt(
-                ((2.0 / n_f * a0).powi(3) * (factorial(n - l - 1) as f64))
+                ((2.0 / (n_f * a0)).powi(3) * (factorial(n - l - 1) as f64))
                     / (2.0 * n_f * (factorial(n + l) as f64)),
             );
             let lag =
                 Poly::laguerre((n - l - 1).try_into().unwrap(), 2.0 * l as f32 + 1.0) * lag_coef;
-            let neg = if m % 2 == 0 { 1.0 } else { -1.0 };
-            let leg_coef = (neg)
-                * (((2 * l + 1) * factorial(l - (m.abs()))) as f64
-                    / (4.0 * PI * factorial(l + m.abs()) as f64))
-                    .sqrt();
-            let leg = Poly::gen_legendre(l.try_into().unwrap(), m.try_into().unwrap()) * leg_coef;
 
             Wavefunction {
                 n,
@@ -44,7 +50,7 @@ mod wavefunction {
                 m,
                 a0,
                 laguerre_poly: lag,
-                legendre_poly: leg,
+                legendre_poly: legendre_poly(l, m),
             }
         }
 
@@ -52,22 +58,93 @@ mod wavefunction {
             let n_f: f64 = self.n as f64;
 
             let p: f64 = 2.0 * r / (n_f * self.a0);
-            return self.laguerre_poly.compute(p) * (-p / 2.0).exp() * (p.powi(self.l));
+            self.laguerre_poly.compute(p) * (-p / 2.0).exp() * (p.powi(self.l))
         }
 
         pub fn angular(&self, theta: f64, phi: f64) -> f64 {
             let complex_angle = num::complex::Complex::new(0.0, self.m as f64 * phi).exp();
-            return self.legendre_poly.compute(theta.cos()) * complex_angle.re();
+            self.legendre_poly.compute(theta.cos()) * complex_angle.re()
+        }
+
+        pub fn wf(&self, r: f64, theta: f64, phi: f64) -> f64 {
+            self.radial(r) * self.angular(theta, phi)
+        }
+
+        pub fn pdf(&self, r: f64, theta: f64, phi: f64) -> f64 {
+            self.wf(r, theta, phi).powi(2)
+        }
+
+        /// the normalised spherical harmonic Y_lm(θ, φ)
+        pub fn ylm(&self, theta: f64, phi: f64) -> Complex64 {
+            let complex_angle = Complex64::new(0.0, self.m as f64 * phi).exp();
+            complex_angle * self.legendre_poly.compute(theta.cos())
         }
 
-        // NOTE: unused for now
+        pub fn n(&self) -> i32 {
+            self.n
+        }
 
-        fn wf(&self, r: f64, theta: f64, phi: f64) -> f64 {
-            return self.radial(r) * self.angular(theta, phi);
+        pub fn l(&self) -> i32 {
+            self.l
         }
 
-        fn pdf(&self, r: f64, theta: f64, phi: f64) -> f64 {
-            return self.wf(r, theta, phi).powi(2);
+        pub fn m(&self) -> i32 {
+            self.m
+        }
+
+        pub fn a0(&self) -> f64 {
+            self.a0
+        }
+
+        /// the Bohr energy -Ry Z² / n² in eV, the a0 passed in is a0 / Z
+        pub fn energy(&self) -> f64 {
+            -RYDBERG_EV / (self.n as f64 * self.a0).powi(2)
+        }
+
+        /// the radial polynomial in ρ = 2r / (n a0), lowest power first, so
+        /// radial(r) = Σ c_i ρ^i e^(-ρ/2) ρ^l
+        pub fn radial_coefficients(&self) -> Vec<f64> {
+            coefficients(&self.laguerre_poly)
+        }
+
+        /// the angular polynomial in cos θ, lowest power first. For odd |m|
+        /// it still has to be multiplied by sin^|m| θ, for even |m| that
+        /// factor is already expanded into it.
+        pub fn angular_coefficients(&self) -> Vec<f64> {
+            coefficients(&self.legendre_poly)
+        }
+    }
+
+    fn coefficients(poly: &Poly) -> Vec<f64> {
+        let coefs = poly.get_coefs();
+        let degree = coefs.keys().copied().max().unwrap_or(0);
+        (0..=degree)
+            .map(|i| coefs.get(&i).copied().unwrap_or(0.))
+            .collect()
+    }
+
+    impl Orbital for Wavefunction {
+        /// the complex eigenstate R_nl(r) Y_lm(θ, φ), as opposed to the real
+        /// orbital that `wf` gives
+        fn psi(&self, r: f64, theta: f64, phi: f64) -> Complex64 {
+            self.radial(r) * self.ylm(theta, phi)
+        }
+
+        fn extent(&self) -> f64 {
+            let n_f = self.n as f64;
+            self.a0 * (2.5 * n_f * n_f + 4.0 * n_f)
+        }
+
+        /// R and Θ are real, so the only imaginary part of ψ*∇ψ comes from the
+        /// φ derivative of e^(imφ): j = m |ψ|² / (r sin θ) φ̂, circulating
+        /// around the z axis
+        fn current(&self, r: f64, theta: f64, phi: f64) -> [f64; 3] {
+            let rho = r * theta.sin();
+            if rho.abs() < f64::EPSILON {
+                return [0.; 3];
+            }
+            let j_phi = self.m as f64 * self.psi(r, theta, phi).norm_sqr() / rho;
+            [-j_phi * phi.sin(), j_phi * phi.cos(), 0.]
         }
     }
 
@@ -75,11 +152,12 @@ mod wavefunction {
     mod test {
         use super::Wavefunction;
         use super::factorial;
+        use crate::particle_gen::Orbital;
         #[test]
         fn test_facorials() {
-            let ans: [i32; 5] = [1, 1, 2, 24, 40320];
-            let input: [i32; 5] = [0, 1, 2, 4, 8];
-            for i in 1..5 {
+            let ans: [i64; 6] = [1, 1, 2, 24, 40320, 6227020800];
+            let input: [i32; 6] = [0, 1, 2, 4, 8, 13];
+            for i in 1..6 {
                 assert_eq!(factorial(input[i]), ans[i]);
             }
         }
@@ -93,12 +171,26 @@ mod wavefunction {
             assert_eq!(diff < 0.00001, diff > -0.00001);
 
             let atom = Wavefunction::new(5, 1, 1, 3.0);
-            let diff: f64 = atom.radial(2.0) - 0.14356;
-            assert_eq!(diff < 0.00001, diff > -0.00001);
+            let diff: f64 = atom.radial(2.0) - 0.005317;
+            assert_eq!(diff < 0.000001, diff > -0.000001);
 
             let atom = Wavefunction::new(5, 3, 1, 1.0);
             let diff: f64 = atom.radial(2.0) - 0.000984;
             assert_eq!(diff < 0.00001, diff > -0.00001);
+
+            // for Z = 2, R_10 = 2 Z^(3/2) e^(-Zr), and ∫ r²R² dr = 1 for any a0
+            let atom = Wavefunction::new(1, 0, 0, 0.5);
+            let diff: f64 = atom.radial(0.3) - 2. * 2f64.powf(1.5) * (-0.6f64).exp();
+            assert!(diff.abs() < 1e-9);
+            for (n, l, a0) in [(1, 0, 0.5), (3, 1, 0.25), (4, 2, 2.0)] {
+                let atom = Wavefunction::new(n, l, 0, a0);
+                let dr = atom.extent() * 2. / 20000.;
+                let norm: f64 = (0..20000)
+                    .map(|i| (i as f64 + 0.5) * dr)
+                    .map(|r| r * r * atom.radial(r).powi(2) * dr)
+                    .sum();
+                assert!((norm - 1.).abs() < 1e-6);
+            }
         }
 
         #[test]
@@ -111,139 +203,575 @@ mod wavefunction {
             let diff: f64 = atom.angular(2.0, 3.0) + 0.316835;
             assert_eq!(diff < 0.001, diff > -0.001);
         }
+
+        #[test]
+        fn test_negative_m() {
+            // ±m differ only in the phase of e^(imφ), not in size
+            for (l, m) in [(1, 1), (2, 1), (2, 2), (3, 2)] {
+                let plus = Wavefunction::new(4, l, m, 1.0);
+                let minus = Wavefunction::new(4, l, -m, 1.0);
+                for theta in [0.3, 1.0, 2.0] {
+                    let (a, b) = (plus.angular(theta, 0.), minus.angular(theta, 0.));
+                    assert!((a.abs() - b.abs()).abs() < 1e-12);
+                }
+            }
+        }
+
+        #[test]
+        fn test_coefficients() {
+            let eval = |c: &[f64], x: f64| c.iter().rev().fold(0., |acc, c| acc * x + c);
+            for (n, l, m) in [(1, 0, 0), (4, 1, 0), (4, 3, 1), (5, 2, -2), (6, 3, 3)] {
+                let atom = Wavefunction::new(n, l, m, 1.0);
+                let r = 3.7;
+                let rho = 2. * r / n as f64;
+                let radial =
+                    eval(&atom.radial_coefficients(), rho) * (-rho / 2.).exp() * rho.powi(l);
+                assert!((radial - atom.radial(r)).abs() < 1e-12);
+
+                let theta: f64 = 0.8;
+                let sin_power = if m % 2 == 0 { 0 } else { m.abs() };
+                let angular =
+                    eval(&atom.angular_coefficients(), theta.cos()) * theta.sin().powi(sin_power);
+                assert!((angular - atom.angular(theta, 0.)).abs() < 1e-12);
+            }
+        }
     }
 }
 
+#[allow(clippy::upper_case_acronyms)]
 mod cdf {
-    #[derive(Copy, Clone)]
+    #[derive(Copy, Clone, Default)]
     struct CDFEntry {
         frac: f64,
         x: f64,
     }
 
-    impl CDFEntry {
-        pub fn new() -> CDFEntry {
-            return CDFEntry { frac: 0., x: 0. };
-        }
-    }
-
-    #[derive(Clone)]
+    #[derive(Clone, Default)]
     pub struct CDF {
         points: Vec<CDFEntry>,
     }
 
     impl CDF {
         pub fn new() -> CDF {
-            CDF { points: Vec::new() }
+            CDF::default()
         }
 
+        // points have to be added in increasing order of both frac and x
         pub fn add_point(&mut self, frac: f64, x: f64) {
-            self.points.append(&mut vec![CDFEntry { frac: frac, x: x }]);
+            self.points.push(CDFEntry { frac, x });
         }
 
-        // PERF: recursive might be slower, switch to iterative?
-        fn recurse_inverse_transform(&self, f: f64, i: usize) -> Option<f64> {
-            if i >= self.points.len() {
-                return None;
-            }
-
-            match i {
-                1 => Some(2.),
-                _ => None,
+        // just a binary search, then linear interpolation between the two
+        // neighbouring points. f is a fraction of the total, in [0, 1)
+        pub fn inverse_transform(&self, f: f64) -> f64 {
+            let (first, last) = match (self.points.first(), self.points.last()) {
+                (Some(first), Some(last)) => (first, last),
+                _ => return 0.,
             };
-            Some(1.0)
-        }
+            let target = first.frac + f * (last.frac - first.frac);
+            let i = self.points.partition_point(|p| p.frac < target);
+            if i == 0 {
+                return first.x;
+            }
+            if i == self.points.len() {
+                return last.x;
+            }
 
-        // just a binary search
-        // TODO: return lambda for actual inverse transform
-        pub fn inverse_transform(&self, f: f64) -> f64 {
-            match self.recurse_inverse_transform(f, self.points.len() / 2) {
-                None => 0.,
-                Some(result) => result,
+            let (lo, hi) = (self.points[i - 1], self.points[i]);
+            if hi.frac == lo.frac {
+                return lo.x;
             }
+            lo.x + (hi.x - lo.x) * (target - lo.frac) / (hi.frac - lo.frac)
         }
     }
 
+    /// marginal CDF over r, then CDFs over θ conditioned on the r bin, then
+    /// CDFs over φ conditioned on the (r, θ) bin
     pub struct CDFTriple {
         pub radial: CDF,
-        pub polar: CDF,
-        pub azimuthal: CDF,
+        pub polar: Vec<CDF>,
+        pub azimuthal: Vec<CDF>,
+        pub dr: f64,
+        pub dtheta: f64,
     }
 
     impl CDFTriple {
-        pub fn new(r: CDF, p: CDF, a: CDF) -> CDFTriple {
+        pub fn new(r: CDF, p: Vec<CDF>, a: Vec<CDF>, dr: f64, dtheta: f64) -> CDFTriple {
             CDFTriple {
                 radial: r,
                 polar: p,
                 azimuthal: a,
+                dr,
+                dtheta,
             }
         }
     }
 }
 
+mod rng {
+    // splitmix64, plenty for scattering points and keeps us off extra crates
+    pub struct Rng(u64);
+
+    impl Rng {
+        pub fn new(seed: u64) -> Rng {
+            Rng(seed)
+        }
+
+        pub fn next_u64(&mut self) -> u64 {
+            self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = self.0;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        }
+
+        // uniform in [0, 1)
+        pub fn next_f64(&mut self) -> f64 {
+            (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+        }
+    }
+}
+
+pub mod coupling;
+pub mod gaussian;
+pub mod parabolic;
+pub mod spinor;
+
+pub use cdf::CDFTriple;
 use cdf::*;
-use wavefunction::*;
+use num::complex::Complex64;
+use rng::Rng;
+use spinor::Spin;
+use std::f64::consts::PI;
+
+/// A one electron state that can be evaluated anywhere around the nucleus.
+/// Anything implementing this can be sampled and drawn.
+pub trait Orbital {
+    fn psi(&self, r: f64, theta: f64, phi: f64) -> Complex64;
+
+    /// radius that encloses nearly all of the density, used to size grids
+    fn extent(&self) -> f64;
+
+    fn density(&self, r: f64, theta: f64, phi: f64) -> f64 {
+        self.psi(r, theta, phi).norm_sqr()
+    }
+
+    /// probability current j = (ħ/μ) Im(ψ*∇ψ) in cartesian components, in
+    /// units where ħ = μ = 1. Central differences unless a state knows better
+    fn current(&self, r: f64, theta: f64, phi: f64) -> [f64; 3] {
+        let h = 1e-5 * self.extent();
+        let centre = spherical_to_cartesian(r, theta, phi);
+        let psi = self.psi(r, theta, phi).conj();
+        let mut j = [0.; 3];
+        for (axis, j_axis) in j.iter_mut().enumerate() {
+            let mut fwd = centre;
+            let mut bwd = centre;
+            fwd[axis] += h;
+            bwd[axis] -= h;
+            let (rf, tf, pf) = cartesian_to_spherical(fwd[0], fwd[1], fwd[2]);
+            let (rb, tb, pb) = cartesian_to_spherical(bwd[0], bwd[1], bwd[2]);
+            let grad = (self.psi(rf, tf, pf) - self.psi(rb, tb, pb)) / (2. * h);
+            *j_axis = (psi * grad).im;
+        }
+        j
+    }
+}
+
+/// Σ c_i |ψ_i⟩, mostly the result of a change of basis
+pub struct Superposition<O: Orbital> {
+    pub terms: Vec<(Complex64, O)>,
+}
+
+impl<O: Orbital> Orbital for Superposition<O> {
+    fn psi(&self, r: f64, theta: f64, phi: f64) -> Complex64 {
+        self.terms
+            .iter()
+            .map(|(c, state)| c * state.psi(r, theta, phi))
+            .sum()
+    }
+
+    fn extent(&self) -> f64 {
+        self.terms
+            .iter()
+            .map(|(_, state)| state.extent())
+            .fold(0., f64::max)
+    }
+}
+
+/// A sampled electron position, along with ψ there for colouring. Spin
+/// orbitals also pick the spin the electron would be measured with there,
+/// and ψ is then that spin's component.
+#[derive(Copy, Clone)]
+pub struct Particle {
+    pub position: [f64; 3],
+    pub psi: Complex64,
+    pub spin: Option<Spin>,
+}
 
 /* NOTE:
     The generation method here takes a reimann sum over the PDF (square of the
     wavefunction) to get a CDF, then uniformly samples its inverse.
     It should be doable to do closed form integration of the PDF instead with
     IBP, treating n,l,m as constants.
+
+    The PDF is tabulated on a spherical grid with the r² sin θ volume element,
+    and the angular CDFs are conditioned on the bins before them, so states
+    that don't separate in r, θ, φ (superpositions, parabolic states) sample
+    exactly the same way as the |nlm⟩ ones.
 */
-pub fn gen_cdf(n: i32, l: i32, m: i32, scale: f64, reso: i32) -> CDFTriple {
-    let a0 = scale * 5.29; // INFO: bohr radius = 5.29 * 10E-11
+pub fn gen_cdf<O: Orbital>(orbital: &O, reso: i32) -> CDFTriple {
+    gen_density_cdf(orbital.extent(), reso, |r, theta, phi| {
+        orbital.density(r, theta, phi)
+    })
+}
 
-    // solve the general form of the wavefunction for this state first
-    let wavefunction = wavefunction::Wavefunction::new(n, l, m, a0);
-    let odd = reso % 2;
+// the tabulation behind gen_cdf, for densities that aren't a single |ψ|²
+fn gen_density_cdf<F>(extent: f64, reso: i32, density: F) -> CDFTriple
+where
+    F: Fn(f64, f64, f64) -> f64,
+{
+    let mut builder = CdfBuilder::new(extent, reso);
+    while !builder.shells(&density, reso as usize) {}
+    builder.finish()
+}
 
-    // use half the resolution steps and exploit symmetry
-    let linspace = 0..((reso + odd) / 2);
+/// gen_cdf's table built a few radial shells at a time, so it can be spread
+/// over frames
+pub struct CdfBuilder {
+    n_r: usize,
+    n_theta: usize,
+    n_phi: usize,
+    dr: f64,
+    rcdf: CDF,
+    pcdfs: Vec<CDF>,
+    acdfs: Vec<CDF>,
+    rtotal: f64,
+}
 
-    let scale = GRID_LIMIT / (reso - 1) as f64; // 10/3
-    let linspace = linspace
-        .into_iter()
-        .map(|x| HALF_GRID_LIMIT - (x as f64 * scale));
+impl CdfBuilder {
+    pub fn new(extent: f64, reso: i32) -> CdfBuilder {
+        let (n_r, n_theta, n_phi) = (reso as usize, (reso as usize).div_ceil(2), reso as usize);
+        let mut rcdf = CDF::new();
+        rcdf.add_point(0., 0.);
+        CdfBuilder {
+            n_r,
+            n_theta,
+            n_phi,
+            dr: extent / n_r as f64,
+            rcdf,
+            pcdfs: Vec::with_capacity(n_r),
+            acdfs: Vec::with_capacity(n_r * n_theta),
+            rtotal: 0.,
+        }
+    }
+
+    /// the density evaluations one shell takes
+    pub fn shell_size(&self) -> usize {
+        self.n_theta * self.n_phi
+    }
+
+    /// tabulates the next `count` shells, and says whether all of them are
+    pub fn shells<F>(&mut self, density: &F, count: usize) -> bool
+    where
+        F: Fn(f64, f64, f64) -> f64,
+    {
+        let dtheta = PI / self.n_theta as f64;
+        let dphi = 2. * PI / self.n_phi as f64;
+        let start = self.pcdfs.len();
+        for i in start..(start + count).min(self.n_r) {
+            let r = (i as f64 + 0.5) * self.dr;
+            let mut pcdf = CDF::new();
+            let mut ptotal: f64 = 0.;
+            pcdf.add_point(ptotal, 0.);
+            for j in 0..self.n_theta {
+                let theta = (j as f64 + 0.5) * dtheta;
+                let mut acdf = CDF::new();
+                let mut atotal: f64 = 0.;
+                acdf.add_point(atotal, 0.);
+                for k in 0..self.n_phi {
+                    let phi = (k as f64 + 0.5) * dphi;
+                    atotal += density(r, theta, phi);
+                    acdf.add_point(atotal, (k + 1) as f64 * dphi);
+                }
+                ptotal += atotal * theta.sin();
+                pcdf.add_point(ptotal, (j + 1) as f64 * dtheta);
+                self.acdfs.push(acdf);
+            }
+            self.rtotal += ptotal * r * r;
+            self.rcdf.add_point(self.rtotal, (i + 1) as f64 * self.dr);
+            self.pcdfs.push(pcdf);
+        }
+        self.pcdfs.len() == self.n_r
+    }
+
+    pub fn finish(self) -> CDFTriple {
+        assert_eq!(self.pcdfs.len(), self.n_r, "the table isn't finished");
+        let dtheta = PI / self.n_theta as f64;
+        CDFTriple::new(self.rcdf, self.pcdfs, self.acdfs, self.dr, dtheta)
+    }
+}
+
+fn sample_cdf(cdfs: &CDFTriple, rng: &mut Rng) -> (f64, f64, f64) {
+    let n_theta = cdfs.azimuthal.len() / cdfs.polar.len();
+
+    let r = cdfs.radial.inverse_transform(rng.next_f64());
+    let i = ((r / cdfs.dr) as usize).min(cdfs.polar.len() - 1);
+    let theta = cdfs.polar[i].inverse_transform(rng.next_f64());
+    let j = ((theta / cdfs.dtheta) as usize).min(n_theta - 1);
+    let phi = cdfs.azimuthal[i * n_theta + j].inverse_transform(rng.next_f64());
+
+    (r, theta, phi)
+}
+
+pub fn gen_particles<O: Orbital>(orbital: &O, count: usize, reso: i32, seed: u64) -> Vec<Particle> {
+    sample_particles(orbital, &gen_cdf(orbital, reso), count, seed)
+}
+
+/// gen_particles from a table made earlier, which can be kept and used again
+/// for any state with the same density
+pub fn sample_particles<O: Orbital>(
+    orbital: &O,
+    cdfs: &CDFTriple,
+    count: usize,
+    seed: u64,
+) -> Vec<Particle> {
+    let mut rng = Rng::new(seed);
+    (0..count)
+        .map(|_| {
+            let (r, theta, phi) = sample_cdf(cdfs, &mut rng);
+            Particle {
+                position: spherical_to_cartesian(r, theta, phi),
+                psi: orbital.psi(r, theta, phi),
+                spin: None,
+            }
+        })
+        .collect()
+}
+
+/* NOTE:
+    Rejection sampling is the brute force alternative: points are drawn
+    uniformly from the ball of radius extent, the same one the CDF tables
+    cover, and kept with probability |ψ|² / bound. It needs no table and has
+    no binning error, but throws most of its points away, more so the
+    further the density is from filling the ball. The bound is the densest
+    point of a grid through the origin with some headroom. If a point ever
+    goes over it, everything kept so far was kept too often, so the bound is
+    raised and the batch starts over.
+*/
+const REJECTION_HEADROOM: f64 = 1.5;
+
+pub fn gen_particles_rejection<O: Orbital>(
+    orbital: &O,
+    count: usize,
+    reso: i32,
+    seed: u64,
+) -> Vec<Particle> {
+    let mut bound = rejection_bound(orbital, reso);
+    sample_particles_rejection(orbital, &mut bound, count, seed)
+}
 
-    let mut rcdf = cdf::CDF::new();
+/// the bound gen_particles_rejection starts from, found once so batches of
+/// the same state can share it
+pub fn rejection_bound<O: Orbital>(orbital: &O, reso: i32) -> f64 {
+    let mut search = BoundSearch::new(reso);
+    while !search.slabs(orbital, reso as usize + 1) {}
+    search.bound()
+}
 
-    let mut rtotal: f64 = 0.;
+/// rejection_bound's grid searched a few slabs at a time, so it can be
+/// spread over frames
+pub struct BoundSearch {
+    half: i32,
+    searched: i32,
+    densest: f64,
+}
 
-    // NOTE: Radial sampling
+impl BoundSearch {
+    pub fn new(reso: i32) -> BoundSearch {
+        BoundSearch {
+            half: (reso / 2).max(1),
+            searched: 0,
+            densest: 0.,
+        }
+    }
 
-    // check positive
-    for i in linspace {
-        rtotal += wavefunction.radial(i).powi(2);
-        rcdf.add_point(rtotal, i);
+    /// the density evaluations one slab takes
+    pub fn slab_size(&self) -> usize {
+        (2 * self.half as usize + 1).pow(2)
+    }
+
+    /// searches the next `count` slabs, and says whether all of them are
+    pub fn slabs<O: Orbital>(&mut self, orbital: &O, count: usize) -> bool {
+        let (extent, half) = (orbital.extent(), self.half);
+        let coord = |i: i32| extent * (i as f64 / half as f64 - 1.);
+        let end = (self.searched + count as i32).min(2 * half + 1);
+        for i in self.searched..end {
+            for j in 0..=2 * half {
+                for k in 0..=2 * half {
+                    let (r, theta, phi) = cartesian_to_spherical(coord(i), coord(j), coord(k));
+                    self.densest = self.densest.max(orbital.density(r, theta, phi));
+                }
+            }
+        }
+        self.searched = end;
+        self.searched == 2 * half + 1
     }
 
-    return CDFTriple::new(rcdf.clone(), rcdf.clone(), rcdf);
+    pub fn bound(&self) -> f64 {
+        self.densest * REJECTION_HEADROOM
+    }
 }
 
-fn sample_cdf(cdfs: CDFTriple) -> f64 {
-    let radial_cdf = cdfs.radial;
-    let polar_cdf = cdfs.polar;
-    let azimuthal_cdf = cdfs.azimuthal;
+/// gen_particles_rejection from a bound found earlier, which is raised if
+/// the batch finds it too low, for the batches after it
+pub fn sample_particles_rejection<O: Orbital>(
+    orbital: &O,
+    bound: &mut f64,
+    count: usize,
+    seed: u64,
+) -> Vec<Particle> {
+    let extent = orbital.extent();
+    let mut rng = Rng::new(seed);
+    let mut particles = Vec::with_capacity(count);
+    while particles.len() < count {
+        let position = [0; 3].map(|_| extent * (2. * rng.next_f64() - 1.));
+        let (r, theta, phi) = cartesian_to_spherical(position[0], position[1], position[2]);
+        if r > extent {
+            continue;
+        }
+        let psi = orbital.psi(r, theta, phi);
+        let density = psi.norm_sqr();
+        if density > *bound {
+            *bound = density * REJECTION_HEADROOM;
+            particles.clear();
+        }
+        if rng.next_f64() * *bound < density {
+            particles.push(Particle {
+                position,
+                psi,
+                spin: None,
+            });
+        }
+    }
+    particles
+}
 
-    // TODO: generate spaced random numbers
+pub fn cartesian_to_spherical(x: f64, y: f64, z: f64) -> (f64, f64, f64) {
+    let r = (x * x + y * y + z * z).sqrt();
+    if r == 0. {
+        return (0., 0., 0.);
+    }
+    (r, (z / r).acos(), y.atan2(x))
+}
 
-    // TODO: sample inverse transform
-    radial_cdf.inverse_transform(0.);
+pub fn spherical_to_cartesian(r: f64, theta: f64, phi: f64) -> [f64; 3] {
+    let (sin_t, cos_t) = theta.sin_cos();
+    let (sin_p, cos_p) = phi.sin_cos();
+    [r * sin_t * cos_p, r * sin_t * sin_p, r * cos_t]
+}
 
-    // TODO: angular sampling
-    // TODO: randomize octant
-    // PERF: check if randomizing octant is better before or after
+/// A point of the probability current field, in cartesian coordinates
+pub struct FlowSample {
+    pub position: [f64; 3],
+    pub current: [f64; 3],
+}
 
-    // TODO: add in angular CDFs
-    return 1.;
+/* NOTE:
+    The current is sampled on a cubic grid over the extent of the state, and
+    only kept where the density is a meaningful fraction of its maximum so the
+    arrows trace out the cloud instead of filling the whole box.
+*/
+pub fn gen_current_field<O: Orbital>(orbital: &O, reso: i32, cutoff: f64) -> Vec<FlowSample> {
+    let extent = orbital.extent();
+    let step = 2. * extent / (reso - 1) as f64;
+    let linspace = || (0..reso).map(move |i| -extent + i as f64 * step);
+
+    let mut points = Vec::new();
+    let mut max_density: f64 = 0.;
+    for x in linspace() {
+        for y in linspace() {
+            for z in linspace() {
+                let (r, theta, phi) = cartesian_to_spherical(x, y, z);
+                let density = orbital.density(r, theta, phi);
+                max_density = max_density.max(density);
+                points.push(([x, y, z], (r, theta, phi), density));
+            }
+        }
+    }
+
+    points
+        .into_iter()
+        .filter(|(_, _, density)| *density > cutoff * max_density)
+        .map(|(position, (r, theta, phi), _)| FlowSample {
+            position,
+            current: orbital.current(r, theta, phi),
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod test {
     use super::gen_cdf;
+    use super::gen_current_field;
+    use super::gen_particles;
+    use super::gen_particles_rejection;
+    use super::sample_particles_rejection;
+    use super::wavefunction::Wavefunction;
+    use crate::particle_gen::Orbital;
 
     #[test]
     fn test_sampler() {
-        gen_cdf(1, 0, 0, 5., 100);
+        gen_cdf(&Wavefunction::new(1, 0, 0, 5.), 100);
+
+        // <r> = a0 (3n² - l(l+1)) / 2
+        for (n, l, m) in [(1, 0, 0), (2, 1, 1), (3, 2, -1)] {
+            let atom = Wavefunction::new(n, l, m, 1.0);
+            let expected = (3 * n * n - l * (l + 1)) as f64 / 2.;
+            for particles in [
+                gen_particles(&atom, 20000, 80, 7),
+                gen_particles_rejection(&atom, 5000, 32, 7),
+            ] {
+                let mean_r = particles
+                    .iter()
+                    .map(|p| p.position.iter().map(|x| x * x).sum::<f64>().sqrt())
+                    .sum::<f64>()
+                    / particles.len() as f64;
+                assert!((mean_r - expected).abs() < 0.05 * expected, "{mean_r}");
+            }
+        }
+
+        // a bound found too low is raised, and both samplers stay in the
+        // same ball
+        let atom = Wavefunction::new(2, 1, 0, 1.0);
+        let mut bound = 1e-9;
+        let particles = sample_particles_rejection(&atom, &mut bound, 2000, 3);
+        assert!(bound > atom.density(2., 0., 0.));
+        for p in particles {
+            let r = p.position.iter().map(|x| x * x).sum::<f64>().sqrt();
+            assert!(r <= atom.extent());
+        }
+    }
+
+    #[test]
+    fn test_current() {
+        // real states carry no current
+        let atom = Wavefunction::new(2, 1, 0, 1.0);
+        assert!(
+            gen_current_field(&atom, 9, 0.01)
+                .iter()
+                .all(|s| s.current == [0.; 3])
+        );
+
+        // m > 0 circulates counter-clockwise around z, m < 0 clockwise
+        for m in [1, -1] {
+            let atom = Wavefunction::new(2, 1, m, 1.0);
+            for s in gen_current_field(&atom, 9, 0.01) {
+                let [x, y, _] = s.position;
+                let [jx, jy, jz] = s.current;
+                assert_eq!(jz, 0.);
+                assert!((x * jx + y * jy).abs() < 1e-12);
+                assert!((x * jy - y * jx) * m as f64 >= 0.);
+            }
+        }
     }
 }
@@ -0,0 +1,94 @@
+// Angular momentum coupling. Every quantum number here is passed in doubled,
+// so half integer spins stay integral: j = 1/2 is written as 1.
+
+use super::wavefunction;
+
+// (a + b + ...)/2 for doubled quantum numbers, None if it isn't a
+// non-negative integer
+fn half(twice: i32) -> Option<i32> {
+    if twice < 0 || twice % 2 != 0 {
+        return None;
+    }
+    Some(twice / 2)
+}
+
+/// ⟨j1 m1; j2 m2 | j m⟩ in the Condon-Shortley convention, via Racah's formula.
+/// All arguments are twice the quantum number they stand for.
+pub fn clebsch_gordan(j1: i32, m1: i32, j2: i32, m2: i32, j: i32, m: i32) -> f64 {
+    if m1 + m2 != m || m1.abs() > j1 || m2.abs() > j2 || m.abs() > j {
+        return 0.;
+    }
+    let triangle = (half(j1 + j2 - j), half(j1 - j2 + j), half(j2 - j1 + j));
+    let (Some(a), Some(b), Some(c)) = triangle else {
+        return 0.;
+    };
+    let (Some(jp1), Some(jm1), Some(jp2), Some(jm2), Some(jp), Some(jm)) = (
+        half(j1 + m1),
+        half(j1 - m1),
+        half(j2 + m2),
+        half(j2 - m2),
+        half(j + m),
+        half(j - m),
+    ) else {
+        return 0.;
+    };
+    let sum = (j1 + j2 + j) / 2;
+    // the products and quotients below are taken in f64
+    let factorial = |n: i32| wavefunction::factorial(n) as f64;
+
+    let prefactor = ((j + 1) as f64 * factorial(a) * factorial(b) * factorial(c)
+        / factorial(sum + 1)
+        * factorial(jp)
+        * factorial(jm)
+        * factorial(jp1)
+        * factorial(jm1)
+        * factorial(jp2)
+        * factorial(jm2))
+    .sqrt();
+
+    // k runs over every value that keeps all the factorials non-negative
+    let k_min = 0.max((j2 - j - m1) / 2).max((j1 + m2 - j) / 2);
+    let k_max = a.min(jm1).min(jp2);
+    let series: f64 = (k_min..=k_max)
+        .map(|k| {
+            let sign = if k % 2 == 0 { 1. } else { -1. };
+            sign / (factorial(k)
+                * factorial(a - k)
+                * factorial(jm1 - k)
+                * factorial(jp2 - k)
+                * factorial((j - j2 + m1) / 2 + k)
+                * factorial((j - j1 - m2) / 2 + k))
+        })
+        .sum();
+
+    prefactor * series
+}
+
+#[cfg(test)]
+mod test {
+    use super::clebsch_gordan;
+
+    #[test]
+    fn test_clebsch_gordan() {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-10;
+
+        // two spin 1/2 into the triplet and singlet
+        assert!(close(clebsch_gordan(1, 1, 1, -1, 2, 0), 0.5_f64.sqrt()));
+        assert!(close(clebsch_gordan(1, -1, 1, 1, 0, 0), -(0.5_f64.sqrt())));
+        assert!(close(clebsch_gordan(1, 1, 1, 1, 2, 2), 1.));
+
+        // l = 1 with s = 1/2 into j = 1/2, m_j = 1/2
+        assert!(close(
+            clebsch_gordan(2, 0, 1, 1, 1, 1),
+            -(1. / 3_f64).sqrt()
+        ));
+        assert!(close(
+            clebsch_gordan(2, 2, 1, -1, 1, 1),
+            (2. / 3_f64).sqrt()
+        ));
+
+        // m doesn't add up, or j out of the triangle
+        assert_eq!(clebsch_gordan(2, 2, 1, 1, 3, 1), 0.);
+        assert_eq!(clebsch_gordan(2, 0, 2, 0, 6, 0), 0.);
+    }
+}
@@ -0,0 +1,397 @@
+use super::wavefunction::legendre_poly;
+use super::{Orbital, spherical_to_cartesian};
+use anyhow::{Context, bail};
+use num::complex::Complex64;
+use scilib::math::polynomial::Poly;
+use std::f64::consts::PI;
+use std::fmt;
+
+/* NOTE:
+    Quantum chemistry codes expand orbitals in gaussians instead of the exact
+    exponentials, since products of gaussians integrate in closed form. A
+    contracted gaussian is a fixed sum of primitives
+        φ = A(x, y, z) Σ c_k N_k e^(-α_k r²)
+    where A is either a cartesian monomial x^i y^j z^k or r^l Y_lm. For l >= 2
+    the two differ: cartesian d shells have 6 functions, spherical ones 5.
+
+    Basis sets are read in the Gaussian94 text format that the Basis Set
+    Exchange hands out. Everything is centred on the nucleus and in units of a0.
+*/
+
+/// STO-3G for H through Ne, bundled so there's always something to compare against
+pub const STO_3G: &str = include_str!("basis/sto-3g.gbs");
+
+#[derive(Clone, Debug)]
+pub struct Shell {
+    pub l: i32,
+    /// (exponent, contraction coefficient) of each primitive
+    pub primitives: Vec<(f64, f64)>,
+}
+
+pub struct BasisSet {
+    elements: Vec<(String, Vec<Shell>)>,
+}
+
+fn shell_l(kind: &str) -> Option<i32> {
+    "SPDFGHI"
+        .find(kind)
+        .filter(|_| kind.len() == 1)
+        .map(|l| l as i32)
+}
+
+// Fortran style exponents show up as 1.0D+00
+fn parse_number(token: &str, line: usize) -> anyhow::Result<f64> {
+    token
+        .replace(['D', 'd'], "E")
+        .parse()
+        .with_context(|| format!("line {line}: expected a number, got {token:?}"))
+}
+
+impl BasisSet {
+    pub fn parse(text: &str) -> anyhow::Result<BasisSet> {
+        let mut elements: Vec<(String, Vec<Shell>)> = Vec::new();
+        let mut current: Option<(String, Vec<Shell>)> = None;
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('!'));
+
+        while let Some((line_no, line)) = lines.next() {
+            if line.starts_with("****") {
+                elements.extend(current.take());
+                continue;
+            }
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+
+            // element header, "C     0"
+            let Some((_, shells)) = current.as_mut() else {
+                current = Some((tokens[0].to_string(), Vec::new()));
+                continue;
+            };
+
+            // shell header, "SP   3   1.00"
+            if tokens.len() < 2 {
+                bail!("line {line_no}: expected a shell header, got {line:?}");
+            }
+            let kind = tokens[0].to_ascii_uppercase();
+            let count: usize = tokens[1]
+                .parse()
+                .with_context(|| format!("line {line_no}: bad primitive count"))?;
+            let scale = match tokens.get(2) {
+                Some(token) => parse_number(token, line_no)?,
+                None => 1.,
+            };
+            let ls: Vec<i32> = match kind.as_str() {
+                "SP" | "L" => vec![0, 1],
+                _ => match shell_l(&kind) {
+                    Some(l) => vec![l],
+                    None => bail!("line {line_no}: unknown shell type {kind:?}"),
+                },
+            };
+
+            let mut new_shells: Vec<Shell> = ls
+                .iter()
+                .map(|&l| Shell {
+                    l,
+                    primitives: Vec::with_capacity(count),
+                })
+                .collect();
+            for _ in 0..count {
+                let Some((line_no, line)) = lines.next() else {
+                    bail!("basis ended in the middle of a {kind} shell");
+                };
+                let numbers = line
+                    .split_whitespace()
+                    .map(|token| parse_number(token, line_no))
+                    .collect::<anyhow::Result<Vec<f64>>>()?;
+                if numbers.len() != ls.len() + 1 {
+                    bail!("line {line_no}: expected {} numbers", ls.len() + 1);
+                }
+                // the scale factor stretches the orbital, so it scales α by its square
+                let exponent = numbers[0] * scale * scale;
+                for (shell, coefficient) in new_shells.iter_mut().zip(&numbers[1..]) {
+                    shell.primitives.push((exponent, *coefficient));
+                }
+            }
+            shells.append(&mut new_shells);
+        }
+        elements.extend(current);
+
+        if elements.is_empty() {
+            bail!("no elements in basis set");
+        }
+        Ok(BasisSet { elements })
+    }
+
+    pub fn elements(&self) -> impl Iterator<Item = &str> {
+        self.elements.iter().map(|(symbol, _)| symbol.as_str())
+    }
+
+    pub fn shells(&self, element: &str) -> Option<&[Shell]> {
+        self.elements
+            .iter()
+            .find(|(symbol, _)| symbol.eq_ignore_ascii_case(element))
+            .map(|(_, shells)| shells.as_slice())
+    }
+}
+
+impl Shell {
+    /// x^i y^j z^k with i + j + k = l, in the usual xx, xy, xz, yy, ... order
+    pub fn cartesian_components(&self) -> Vec<[i32; 3]> {
+        let l = self.l;
+        (0..=l)
+            .rev()
+            .flat_map(|i| (0..=l - i).rev().map(move |j| [i, j, l - i - j]))
+            .collect()
+    }
+
+    pub fn basis_functions(&self, spherical: bool) -> Vec<ContractedGaussian> {
+        if spherical {
+            (-self.l..=self.l)
+                .map(|m| ContractedGaussian::spherical(self, m))
+                .collect()
+        } else {
+            self.cartesian_components()
+                .into_iter()
+                .map(|powers| ContractedGaussian::cartesian(self, powers))
+                .collect()
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum AngularForm {
+    Cartesian([i32; 3]),
+    Spherical(i32),
+}
+
+pub struct ContractedGaussian {
+    l: i32,
+    form: AngularForm,
+    // (α, c N) with the contraction normalised as a whole
+    primitives: Vec<(f64, f64)>,
+    legendre_poly: Option<Poly>,
+}
+
+fn double_factorial(n: i32) -> f64 {
+    (1..=n).rev().step_by(2).map(|i| i as f64).product()
+}
+
+// ∫ x^2i e^(-p x²) dx over the whole line
+fn gaussian_moment(i: i32, p: f64) -> f64 {
+    double_factorial(2 * i - 1) / (2. * p).powi(i) * (PI / p).sqrt()
+}
+
+// ∫ r^(2l+2) e^(-p r²) dr from 0, Γ(l + 3/2) / 2p^(l + 3/2)
+fn radial_moment(l: i32, p: f64) -> f64 {
+    let gamma = double_factorial(2 * l + 1) * PI.sqrt() / 2_f64.powi(l + 1);
+    gamma / (2. * p.powf(l as f64 + 1.5))
+}
+
+impl ContractedGaussian {
+    pub fn cartesian(shell: &Shell, powers: [i32; 3]) -> ContractedGaussian {
+        assert_eq!(powers.iter().sum::<i32>(), shell.l);
+        let self_overlap = |p: f64| powers.iter().map(|&i| gaussian_moment(i, p)).product();
+        ContractedGaussian {
+            l: shell.l,
+            form: AngularForm::Cartesian(powers),
+            primitives: normalise(&shell.primitives, self_overlap),
+            legendre_poly: None,
+        }
+    }
+
+    pub fn spherical(shell: &Shell, m: i32) -> ContractedGaussian {
+        assert!(m.abs() <= shell.l);
+        let l = shell.l;
+        ContractedGaussian {
+            l,
+            form: AngularForm::Spherical(m),
+            primitives: normalise(&shell.primitives, |p| radial_moment(l, p)),
+            legendre_poly: Some(legendre_poly(l, m)),
+        }
+    }
+
+    pub fn l(&self) -> i32 {
+        self.l
+    }
+
+    pub fn form(&self) -> AngularForm {
+        self.form
+    }
+
+    fn radial_sum(&self, r: f64) -> f64 {
+        self.primitives
+            .iter()
+            .map(|(alpha, c)| c * (-alpha * r * r).exp())
+            .sum()
+    }
+}
+
+// scales the coefficients so each primitive and then the whole contraction
+// is normalised, `overlap(p)` being ∫ of the angular part times e^(-p r²)
+fn normalise<F: Fn(f64) -> f64>(primitives: &[(f64, f64)], overlap: F) -> Vec<(f64, f64)> {
+    let scaled: Vec<(f64, f64)> = primitives
+        .iter()
+        .map(|(alpha, c)| (*alpha, c / overlap(2. * alpha).sqrt()))
+        .collect();
+    let overlap = &overlap;
+    let total: f64 = scaled
+        .iter()
+        .flat_map(|(a, ca)| scaled.iter().map(move |(b, cb)| ca * cb * overlap(a + b)))
+        .sum();
+    scaled
+        .into_iter()
+        .map(|(alpha, c)| (alpha, c / total.sqrt()))
+        .collect()
+}
+
+impl Orbital for ContractedGaussian {
+    fn psi(&self, r: f64, theta: f64, phi: f64) -> Complex64 {
+        let radial = self.radial_sum(r);
+        match (self.form, &self.legendre_poly) {
+            (AngularForm::Spherical(m), Some(legendre)) => {
+                let complex_angle = Complex64::new(0.0, m as f64 * phi).exp();
+                complex_angle * legendre.compute(theta.cos()) * r.powi(self.l) * radial
+            }
+            (AngularForm::Cartesian(powers), _) => {
+                let position = spherical_to_cartesian(r, theta, phi);
+                let monomial: f64 = position
+                    .iter()
+                    .zip(powers)
+                    .map(|(x, i)| x.powi(i))
+                    .product();
+                Complex64::from(monomial * radial)
+            }
+            (AngularForm::Spherical(_), None) => unreachable!(),
+        }
+    }
+
+    // past this the most diffuse primitive is down by e^-12
+    fn extent(&self) -> f64 {
+        let alpha_min = self
+            .primitives
+            .iter()
+            .map(|(alpha, _)| *alpha)
+            .fold(f64::INFINITY, f64::min);
+        ((12. + self.l as f64) / alpha_min).sqrt()
+    }
+}
+
+/// how close an approximate state is to an exact one
+pub struct Comparison {
+    /// ⟨approx|exact⟩
+    pub overlap: Complex64,
+    /// |⟨approx|exact⟩|² over both norms, 1 when they match up to a phase
+    pub fidelity: f64,
+    /// the L2 norm of approx - exact
+    pub l2_error: f64,
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "overlap {:.6}{:+.6}i, fidelity {:.6}, L2 error {:.6}",
+            self.overlap.re, self.overlap.im, self.fidelity, self.l2_error
+        )
+    }
+}
+
+/// midpoint sum over a spherical grid out to the larger of the two extents,
+/// with 4 reso radial, reso polar and 2 reso azimuthal steps
+pub fn compare<A: Orbital, B: Orbital>(approx: &A, exact: &B, reso: i32) -> Comparison {
+    let (n_r, n_theta, n_phi) = (4 * reso, reso, 2 * reso);
+    let dr = approx.extent().max(exact.extent()) / n_r as f64;
+    let dtheta = PI / n_theta as f64;
+    let dphi = 2. * PI / n_phi as f64;
+
+    let mut overlap = Complex64::new(0., 0.);
+    let (mut norm_a, mut norm_b, mut error) = (0., 0., 0.);
+    for i in 0..n_r {
+        let r = (i as f64 + 0.5) * dr;
+        for j in 0..n_theta {
+            let theta = (j as f64 + 0.5) * dtheta;
+            let volume = r * r * theta.sin() * dr * dtheta * dphi;
+            for k in 0..n_phi {
+                let phi = (k as f64 + 0.5) * dphi;
+                let a = approx.psi(r, theta, phi);
+                let b = exact.psi(r, theta, phi);
+                overlap += a.conj() * b * volume;
+                norm_a += a.norm_sqr() * volume;
+                norm_b += b.norm_sqr() * volume;
+                error += (a - b).norm_sqr() * volume;
+            }
+        }
+    }
+
+    Comparison {
+        overlap,
+        fidelity: overlap.norm_sqr() / (norm_a * norm_b),
+        l2_error: error.sqrt(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::wavefunction::Wavefunction;
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let basis = BasisSet::parse(STO_3G).unwrap();
+        assert_eq!(basis.elements().count(), 10);
+
+        let hydrogen = basis.shells("H").unwrap();
+        assert_eq!(hydrogen.len(), 1);
+        assert_eq!(hydrogen[0].l, 0);
+        assert_eq!(hydrogen[0].primitives.len(), 3);
+
+        // SP splits into an s and a p shell on the same exponents
+        let carbon = basis.shells("c").unwrap();
+        assert_eq!(carbon.iter().map(|s| s.l).collect::<Vec<_>>(), [0, 0, 1]);
+        assert_eq!(carbon[1].primitives[0].0, carbon[2].primitives[0].0);
+
+        let scaled = BasisSet::parse("X 0\nD 1 2.0\n 0.5D+00 1.0D+00\n****\n").unwrap();
+        let shell = &scaled.shells("X").unwrap()[0];
+        assert_eq!((shell.l, shell.primitives[0]), (2, (2.0, 1.0)));
+        assert_eq!(shell.cartesian_components().len(), 6);
+        assert_eq!(shell.basis_functions(true).len(), 5);
+
+        assert!(BasisSet::parse("H 0\nQ 1 1.0\n 1.0 1.0\n").is_err());
+        assert!(BasisSet::parse("H 0\nS 2 1.0\n 1.0 1.0\n").is_err());
+        assert!(BasisSet::parse("").is_err());
+    }
+
+    #[test]
+    fn test_normalised() {
+        let basis = BasisSet::parse("X 0\nD 2 1.0\n 1.2 0.6\n 0.3 0.5\n****\n").unwrap();
+        let shell = &basis.shells("X").unwrap()[0];
+        for spherical in [false, true] {
+            for function in shell.basis_functions(spherical) {
+                let norm = compare(&function, &function, 32).overlap.re;
+                assert!((norm - 1.).abs() < 5e-3, "{:?} {norm}", function.form());
+            }
+        }
+    }
+
+    #[test]
+    fn test_sto_3g_hydrogen() {
+        // the STO-3G fit of a ζ = 1 slater 1s, which is exactly hydrogen's 1s
+        let basis = BasisSet::parse(
+            "H 0\nS 3 1.00\n 2.227660584 0.15432897\n 0.405771156 0.53532814\n 0.109817510 0.44463454\n****\n",
+        )
+        .unwrap();
+        let gaussian = ContractedGaussian::spherical(&basis.shells("H").unwrap()[0], 0);
+        let exact = Wavefunction::new(1, 0, 0, 1.0);
+        let comparison = compare(&gaussian, &exact, 24);
+        assert!(comparison.fidelity > 0.99, "{comparison}");
+        assert!(comparison.fidelity < 1., "{comparison}");
+        assert!(comparison.l2_error < 0.1, "{comparison}");
+
+        // a p function can't overlap an s state
+        let basis = BasisSet::parse(STO_3G).unwrap();
+        let p = ContractedGaussian::cartesian(&basis.shells("Li").unwrap()[2], [0, 0, 1]);
+        assert!(compare(&p, &exact, 24).fidelity < 1e-6);
+    }
+}
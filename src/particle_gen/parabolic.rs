@@ -0,0 +1,204 @@
+use super::coupling::clebsch_gordan;
+use super::wavefunction::Wavefunction;
+use super::wavefunction::factorial;
+use super::{Orbital, Superposition};
+use num::complex::Complex64;
+use scilib::math::polynomial::Poly;
+use std::f64::consts::PI;
+
+/* NOTE:
+    Hydrogen also separates in parabolic coordinates ξ = r + z, η = r - z, φ.
+    The states are labelled by n1, n2 >= 0 and m, with n = n1 + n2 + |m| + 1,
+    and unlike |nlm⟩ they have a permanent dipole along z, which is why they
+    are the eigenstates of the linear Stark effect.
+
+    With ρ1 = ξ / n a0 and ρ2 = η / n a0:
+        ψ = e^(imφ) / (n² √(π a0³)) f_n1(ρ1) f_n2(ρ2)
+        f_p(ρ) = √(p! / (p + |m|)!) e^(-ρ/2) ρ^(|m|/2) L_p^|m|(ρ)
+*/
+pub struct ParabolicState {
+    n1: i32,
+    n2: i32,
+    m: i32,
+    a0: f64,
+    xi_poly: Poly,
+    eta_poly: Poly,
+}
+
+impl ParabolicState {
+    pub fn new(n1: i32, n2: i32, m: i32, a0: f64) -> ParabolicState {
+        let abs_m = m.abs();
+        let n = (n1 + n2 + abs_m + 1) as f64;
+        let norm = 1. / (n * n * (PI * a0.powi(3)).sqrt());
+        let f_coef = |p: i32| (factorial(p) as f64 / factorial(p + abs_m) as f64).sqrt();
+
+        let xi_poly = Poly::laguerre(n1.try_into().unwrap(), abs_m) * (norm * f_coef(n1));
+        let eta_poly = Poly::laguerre(n2.try_into().unwrap(), abs_m) * f_coef(n2);
+
+        ParabolicState {
+            n1,
+            n2,
+            m,
+            a0,
+            xi_poly,
+            eta_poly,
+        }
+    }
+
+    pub fn n(&self) -> i32 {
+        self.n1 + self.n2 + self.m.abs() + 1
+    }
+
+    pub fn n1(&self) -> i32 {
+        self.n1
+    }
+
+    pub fn n2(&self) -> i32 {
+        self.n2
+    }
+
+    pub fn m(&self) -> i32 {
+        self.m
+    }
+
+    /// ⟨n l m | n1 n2 m⟩ for every l the state mixes
+    ///
+    /// Both bases are built from two angular momenta j = (n - 1)/2, with
+    /// m1 = (m + n1 - n2)/2 and m2 = (m - n1 + n2)/2, so the overlap is a
+    /// Clebsch-Gordan coefficient. The (-1)^(n2 + l - |m|) matches the phases
+    /// of `Wavefunction` to the ones above.
+    pub fn spherical_coefficients(&self) -> Vec<(i32, f64)> {
+        let n = self.n();
+        let j = n - 1; // doubled
+        let m1 = self.m + self.n1 - self.n2;
+        let m2 = self.m - self.n1 + self.n2;
+
+        (self.m.abs()..n)
+            .map(|l| {
+                let sign = if (self.n2 + l - self.m.abs()) % 2 == 0 {
+                    1.
+                } else {
+                    -1.
+                };
+                (l, sign * clebsch_gordan(j, m1, j, m2, 2 * l, 2 * self.m))
+            })
+            .collect()
+    }
+
+    /// the same state written out in the spherical |nlm⟩ basis
+    pub fn to_spherical(&self) -> Superposition<Wavefunction> {
+        let n = self.n();
+        Superposition {
+            terms: self
+                .spherical_coefficients()
+                .into_iter()
+                .map(|(l, c)| (Complex64::from(c), Wavefunction::new(n, l, self.m, self.a0)))
+                .collect(),
+        }
+    }
+
+    /// a spherical |nlm⟩ written out in the parabolic basis; the transform is
+    /// a real orthogonal matrix, so it is the same coefficients read the
+    /// other way
+    pub fn from_spherical(state: &Wavefunction) -> Superposition<ParabolicState> {
+        let (n, l, m) = (state.n(), state.l(), state.m());
+        Superposition {
+            terms: (0..n - m.abs())
+                .map(|n1| ParabolicState::new(n1, n - m.abs() - 1 - n1, m, state.a0()))
+                .filter_map(|parabolic| {
+                    let c = parabolic
+                        .spherical_coefficients()
+                        .into_iter()
+                        .find(|(l_i, _)| *l_i == l)?
+                        .1;
+                    Some((Complex64::from(c), parabolic))
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Orbital for ParabolicState {
+    fn psi(&self, r: f64, theta: f64, phi: f64) -> Complex64 {
+        let abs_m = self.m.abs();
+        let scale = self.n() as f64 * self.a0;
+        let z = r * theta.cos();
+        let rho1 = (r + z) / scale;
+        let rho2 = (r - z).max(0.) / scale;
+
+        let f = |poly: &Poly, rho: f64| {
+            poly.compute(rho) * (-rho / 2.).exp() * rho.powf(abs_m as f64 / 2.)
+        };
+        let complex_angle = Complex64::new(0.0, self.m as f64 * phi).exp();
+        complex_angle * f(&self.xi_poly, rho1) * f(&self.eta_poly, rho2)
+    }
+
+    fn extent(&self) -> f64 {
+        let n_f = self.n() as f64;
+        self.a0 * (2.5 * n_f * n_f + 4.0 * n_f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::wavefunction::Wavefunction;
+    use super::super::{Orbital, gen_particles};
+    use super::ParabolicState;
+
+    #[test]
+    fn test_normalised() {
+        // midpoint sum over a spherical grid, the density doesn't depend on φ
+        for (n1, n2, m) in [(0, 0, 0), (1, 0, 0), (0, 1, 1), (1, 1, -1)] {
+            let state = ParabolicState::new(n1, n2, m, 1.0);
+            let (steps_r, steps_t) = (400, 200);
+            let dr = state.extent() / steps_r as f64;
+            let dt = std::f64::consts::PI / steps_t as f64;
+            let mut total = 0.;
+            for i in 0..steps_r {
+                let r = (i as f64 + 0.5) * dr;
+                for j in 0..steps_t {
+                    let t = (j as f64 + 0.5) * dt;
+                    total += state.density(r, t, 0.) * r * r * t.sin() * dr * dt;
+                }
+            }
+            total *= 2. * std::f64::consts::PI;
+            assert!((total - 1.).abs() < 1e-3, "{n1} {n2} {m}: {total}");
+        }
+    }
+
+    #[test]
+    fn test_change_of_basis() {
+        let points = [
+            (0.7, 0.3, 0.2),
+            (3.0, 1.2, 2.0),
+            (5.5, 2.6, -1.0),
+            (9.0, 1.7, 0.4),
+        ];
+        for (n1, n2, m) in [(1, 0, 0), (0, 1, 0), (2, 0, 1), (1, 1, 0), (0, 1, -2)] {
+            let direct = ParabolicState::new(n1, n2, m, 1.0);
+            let spherical = direct.to_spherical();
+            let total: f64 = spherical.terms.iter().map(|(c, _)| c.norm_sqr()).sum();
+            assert!((total - 1.).abs() < 1e-10);
+            for (r, t, p) in points {
+                let diff = direct.psi(r, t, p) - spherical.psi(r, t, p);
+                assert!(diff.norm() < 1e-8, "{n1} {n2} {m} at {r}: {diff}");
+            }
+        }
+
+        let state = Wavefunction::new(3, 1, 0, 1.0);
+        let parabolic = ParabolicState::from_spherical(&state);
+        for (r, t, p) in points {
+            let diff = state.psi(r, t, p) - parabolic.psi(r, t, p);
+            assert!(diff.norm() < 1e-8);
+        }
+    }
+
+    #[test]
+    fn test_dipole() {
+        // n1 > n2 leans towards +z
+        let state = ParabolicState::new(1, 0, 0, 1.0);
+        let particles = gen_particles(&state, 4000, 60, 1);
+        let mean_z = particles.iter().map(|p| p.position[2]).sum::<f64>() / 4000.;
+        assert!(mean_z > 1.);
+    }
+}
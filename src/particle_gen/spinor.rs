@@ -0,0 +1,203 @@
+use super::coupling::clebsch_gordan;
+use super::rng::Rng;
+use super::wavefunction::Wavefunction;
+use super::{CDFTriple, Orbital, Particle, gen_density_cdf, sample_cdf, spherical_to_cartesian};
+use num::complex::Complex64;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Spin {
+    Up,
+    Down,
+}
+
+/* NOTE:
+    Spin-orbit coupling mixes the orbital and the spin of the electron, so the
+    fine structure eigenstates are
+        |n l j m_j⟩ = Σ ⟨l, m_j - m_s; 1/2, m_s | j m_j⟩ |n l (m_j - m_s)⟩ |m_s⟩
+    over m_s = ±1/2, with j = l ± 1/2. The two spin components have different
+    m, so spin up and spin down have visibly different shapes.
+
+    j and m_j are half integers, so like in `coupling` they are passed doubled.
+*/
+pub struct SpinOrbital {
+    n: i32,
+    l: i32,
+    j: i32,
+    m_j: i32,
+    up: Option<(f64, Wavefunction)>,
+    down: Option<(f64, Wavefunction)>,
+}
+
+impl SpinOrbital {
+    /// `None` unless j is l ± 1/2 and m_j is in -j..=j
+    pub fn new(n: i32, l: i32, j: i32, m_j: i32, a0: f64) -> Option<SpinOrbital> {
+        if !(j == 2 * l + 1 || (l > 0 && j == 2 * l - 1)) || m_j.abs() > j || m_j % 2 == 0 {
+            return None;
+        }
+
+        // m_s doubled is ±1, and the orbital part has to have |m| <= l
+        let component = |m_s: i32| {
+            let m = (m_j - m_s) / 2;
+            if m.abs() > l {
+                return None;
+            }
+            let c = clebsch_gordan(2 * l, 2 * m, 1, m_s, j, m_j);
+            (c != 0.).then(|| (c, Wavefunction::new(n, l, m, a0)))
+        };
+
+        Some(SpinOrbital {
+            n,
+            l,
+            j,
+            m_j,
+            up: component(1),
+            down: component(-1),
+        })
+    }
+
+    pub fn n(&self) -> i32 {
+        self.n
+    }
+
+    pub fn l(&self) -> i32 {
+        self.l
+    }
+
+    /// doubled, 3 is j = 3/2
+    pub fn j(&self) -> i32 {
+        self.j
+    }
+
+    /// doubled, like j
+    pub fn m_j(&self) -> i32 {
+        self.m_j
+    }
+
+    /// the Clebsch-Gordan weight and orbital of one spin component
+    pub fn component(&self, spin: Spin) -> Option<&(f64, Wavefunction)> {
+        match spin {
+            Spin::Up => self.up.as_ref(),
+            Spin::Down => self.down.as_ref(),
+        }
+    }
+
+    /// both components of the two component wavefunction, up first
+    pub fn spinor(&self, r: f64, theta: f64, phi: f64) -> [Complex64; 2] {
+        [Spin::Up, Spin::Down].map(|spin| match self.component(spin) {
+            Some((c, state)) => *c * state.psi(r, theta, phi),
+            None => Complex64::new(0., 0.),
+        })
+    }
+
+    pub fn density(&self, r: f64, theta: f64, phi: f64) -> f64 {
+        self.spinor(r, theta, phi)
+            .iter()
+            .map(|c| c.norm_sqr())
+            .sum()
+    }
+
+    /// the spin components are orthogonal, so their currents just add
+    pub fn current(&self, r: f64, theta: f64, phi: f64) -> [f64; 3] {
+        let mut j = [0.; 3];
+        for (c, state) in self.up.iter().chain(self.down.iter()) {
+            let part = state.current(r, theta, phi);
+            for axis in 0..3 {
+                j[axis] += c * c * part[axis];
+            }
+        }
+        j
+    }
+
+    pub fn extent(&self) -> f64 {
+        self.up
+            .iter()
+            .chain(self.down.iter())
+            .map(|(_, state)| state.extent())
+            .fold(0., f64::max)
+    }
+}
+
+// Samples the total density, then picks each electron's spin with the
+// probability of measuring it there, so the up and down clouds come out in
+// the right proportions and overlap where they should.
+pub fn gen_particles(orbital: &SpinOrbital, count: usize, reso: i32, seed: u64) -> Vec<Particle> {
+    sample_particles(orbital, &gen_cdf(orbital, reso), count, seed)
+}
+
+/// the table of the summed density of both components
+pub fn gen_cdf(orbital: &SpinOrbital, reso: i32) -> CDFTriple {
+    gen_density_cdf(orbital.extent(), reso, |r, theta, phi| {
+        orbital.density(r, theta, phi)
+    })
+}
+
+pub fn sample_particles(
+    orbital: &SpinOrbital,
+    cdfs: &CDFTriple,
+    count: usize,
+    seed: u64,
+) -> Vec<Particle> {
+    let mut rng = Rng::new(seed);
+    (0..count)
+        .map(|_| {
+            let (r, theta, phi) = sample_cdf(cdfs, &mut rng);
+            let [up, down] = orbital.spinor(r, theta, phi);
+            // on a node of both components either spin is as likely, rather
+            // than NaN always losing the comparison below
+            let total = up.norm_sqr() + down.norm_sqr();
+            let p_up = if total > 0. {
+                up.norm_sqr() / total
+            } else {
+                0.5
+            };
+            let (spin, psi) = if rng.next_f64() < p_up {
+                (Spin::Up, up)
+            } else {
+                (Spin::Down, down)
+            };
+            Particle {
+                position: spherical_to_cartesian(r, theta, phi),
+                psi,
+                spin: Some(spin),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Spin, SpinOrbital, gen_particles};
+
+    #[test]
+    fn test_components() {
+        // |2 1 3/2 3/2⟩ is a pure |2 1 1⟩ spin up
+        let state = SpinOrbital::new(2, 1, 3, 3, 1.0).unwrap();
+        assert!(state.component(Spin::Down).is_none());
+        assert_eq!(state.component(Spin::Up).unwrap().1.m(), 1);
+
+        // |2 1 1/2 1/2⟩ = -√(1/3) |2 1 0⟩↑ + √(2/3) |2 1 1⟩↓
+        let state = SpinOrbital::new(2, 1, 1, 1, 1.0).unwrap();
+        let (up, up_state) = state.component(Spin::Up).unwrap();
+        let (down, down_state) = state.component(Spin::Down).unwrap();
+        assert_eq!((up_state.m(), down_state.m()), (0, 1));
+        assert!((up + (1. / 3_f64).sqrt()).abs() < 1e-10);
+        assert!((down - (2. / 3_f64).sqrt()).abs() < 1e-10);
+
+        // j = l - 1/2 doesn't exist for s states, and m_j has to fit in j
+        assert!(SpinOrbital::new(1, 0, -1, 1, 1.0).is_none());
+        assert!(SpinOrbital::new(3, 2, 1, 3, 1.0).is_none());
+        assert!(SpinOrbital::new(3, 2, 3, 2, 1.0).is_none());
+    }
+
+    #[test]
+    fn test_spin_fractions() {
+        let state = SpinOrbital::new(2, 1, 1, 1, 1.0).unwrap();
+        let particles = gen_particles(&state, 6000, 40, 3);
+        let up = particles
+            .iter()
+            .filter(|p| p.spin == Some(Spin::Up))
+            .count() as f64
+            / particles.len() as f64;
+        assert!((up - 1. / 3.).abs() < 0.03, "{up}");
+    }
+}